@@ -0,0 +1,92 @@
+use actix_web::rt::System;
+use clap::{App, Arg};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::hmac_server::{self, Comparison, Config};
+use std::process;
+use std::time::Duration;
+
+fn cli() -> App<'static, 'static> {
+  // Anything a Duration can't hold, including infinity, NaN and negative
+  // numbers, is turned down here rather than panicking later
+  let is_millis = |value: String| {
+    value
+      .parse::<f64>()
+      .ok()
+      .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+      .map(|_| ())
+      .ok_or_else(|| format!("{} is not a number of milliseconds", value))
+  };
+
+  App::new("hmac-server")
+    .about("Serves HMAC-SHA1 checks at /hmac/{file}/{signature} for timing attacks")
+    .arg(
+      Arg::with_name("bind")
+        .help("Address to listen on. Port 0 picks a free one")
+        .long("bind")
+        .default_value("127.0.0.1:9000"),
+    )
+    .arg(
+      Arg::with_name("delay")
+        .help("Milliseconds comparing each byte takes")
+        .long("delay")
+        .default_value("50")
+        .validator(is_millis),
+    )
+    .arg(
+      Arg::with_name("jitter")
+        .help("Up to how many more milliseconds, at random, each check takes")
+        .long("jitter")
+        .default_value("0")
+        .validator(is_millis),
+    )
+    .arg(
+      Arg::with_name("comparison")
+        .help("How signatures are compared")
+        .long("comparison")
+        .possible_values(&["early-exit", "constant-time", "double-hmac"])
+        .default_value("early-exit"),
+    )
+    .arg(
+      Arg::with_name("key")
+        .help("HMAC key in hex. Random when not given")
+        .long("key")
+        .takes_value(true),
+    )
+}
+
+fn main() {
+  let matches = cli().get_matches();
+  let millis = |name| {
+    Duration::from_secs_f64(matches.value_of(name).unwrap().parse::<f64>().unwrap() / 1000.0)
+  };
+  let key = match matches.value_of("key").map(|key| key.from_hex()) {
+    None => random_64().to_vec(),
+    Some(Ok(key)) => key,
+    Some(Err(err)) => {
+      eprintln!("Bad key: {}", err);
+      process::exit(1);
+    }
+  };
+  let config = Config {
+    delay: millis("delay"),
+    jitter: millis("jitter"),
+    comparison: matches
+      .value_of("comparison")
+      .unwrap()
+      .parse::<Comparison>()
+      .unwrap(),
+    ..Config::new(&key)
+  };
+
+  let bind = matches.value_of("bind").unwrap().to_string();
+  match hmac_server::start(bind, config) {
+    Ok((server, addr)) => {
+      println!("Listening on http://{}", addr);
+      System::new().block_on(server).ok();
+    }
+    Err(err) => {
+      eprintln!("Can't listen: {}", err);
+      process::exit(1);
+    }
+  }
+}
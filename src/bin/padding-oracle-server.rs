@@ -0,0 +1,30 @@
+use actix_web::rt::System;
+use clap::{App, Arg};
+use cryptopals_rust::padding_oracle_server;
+use std::process;
+
+fn cli() -> App<'static, 'static> {
+  App::new("padding-oracle-server")
+    .about("Hands out CBC session tokens at /padding-oracle/token and leaks their padding")
+    .arg(
+      Arg::with_name("bind")
+        .help("Address to listen on. Port 0 picks a free one")
+        .long("bind")
+        .default_value("127.0.0.1:9000"),
+    )
+}
+
+fn main() {
+  let matches = cli().get_matches();
+  let bind = matches.value_of("bind").unwrap().to_string();
+  match padding_oracle_server::start(bind) {
+    Ok((server, addr)) => {
+      println!("Listening on http://{}", addr);
+      System::new().block_on(server).ok();
+    }
+    Err(err) => {
+      eprintln!("Can't listen: {}", err);
+      process::exit(1);
+    }
+  }
+}
@@ -1,3 +1,6 @@
+//! Hex and Base64 codecs, XOR, PKCS#7 padding and random bytes.
+
+use crate::error::{Error, Result};
 use std::cmp;
 use std::str;
 extern crate base64;
@@ -33,16 +36,16 @@ pub fn random_64() -> [u8; 64] {
   value
 }
 
-pub fn from_hex(encoded: &str) -> Vec<u8> {
-  hex::decode(encoded).expect("Failed to decode hex string")
+pub fn from_hex(encoded: &str) -> Result<Vec<u8>> {
+  hex::decode(encoded).map_err(|_| Error::InvalidHex)
 }
 
 pub fn to_hex(bytes: &[u8]) -> String {
   hex::encode(bytes)
 }
 
-pub fn from_base64(encoded: &str) -> Vec<u8> {
-  base64::decode(encoded).expect("Failed to decode Base64 string")
+pub fn from_base64(encoded: &str) -> Result<Vec<u8>> {
+  base64::decode(encoded).map_err(|_| Error::InvalidBase64)
 }
 
 pub fn to_base64(bytes: &[u8]) -> String {
@@ -79,6 +82,15 @@ pub fn edit_distance(a: &[u8], b: &[u8]) -> u32 {
   distance
 }
 
+/// Compares in a time that depends only on the lengths, never on where the
+/// bytes differ.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+  if a.len() != b.len() {
+    return false;
+  }
+  a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 pub fn cycled_chunk(a: &[u8], len: usize) -> Vec<Vec<u8>> {
   let mut chunks: Vec<Vec<u8>> = vec![Vec::new(); len];
   for chunk in a.chunks(len) {
@@ -94,29 +106,29 @@ pub fn pad_pkcs7(a: &[u8], block_size: usize) -> Vec<u8> {
   [a, &vec![n as u8; n][..]].concat()
 }
 
-pub fn strip_pkcs7(a: &[u8]) -> Result<Vec<u8>, &'static str> {
-  let n = a[a.len() - 1];
-  if n as usize >= a.len() {
-    return Err("Invalid padding");
+pub fn strip_pkcs7(a: &[u8]) -> Result<Vec<u8>> {
+  let n = *a.last().ok_or(Error::BadPadding)?;
+  if n == 0 || n as usize > a.len() {
+    return Err(Error::BadPadding);
   }
   let (message, padding) = a.split_at(a.len() - n as usize);
-  if &vec![n; n as usize][..] == padding {
+  if padding.iter().all(|&b| b == n) {
     Ok(message.to_owned())
   } else {
-    Err("Invalid padding")
+    Err(Error::BadPadding)
   }
 }
 
 pub trait Bytes {
   fn to_hex(&self) -> String;
   fn to_base64(&self) -> String;
-  fn as_string(&self) -> String;
+  fn as_string(&self) -> Result<String>;
   fn pad_pkcs7(&self, block_size: usize) -> Vec<u8>;
 }
 
 pub trait EncodedBytes {
-  fn from_hex(&self) -> Vec<u8>;
-  fn from_base64(&self) -> Vec<u8>;
+  fn from_hex(&self) -> Result<Vec<u8>>;
+  fn from_base64(&self) -> Result<Vec<u8>>;
 }
 
 impl Bytes for Vec<u8> {
@@ -128,8 +140,10 @@ impl Bytes for Vec<u8> {
     to_base64(&self)
   }
 
-  fn as_string(&self) -> String {
-    str::from_utf8(&self).unwrap().to_string()
+  fn as_string(&self) -> Result<String> {
+    str::from_utf8(&self)
+      .map(|s| s.to_string())
+      .map_err(|_| Error::NotUtf8)
   }
 
   fn pad_pkcs7(&self, block_size: usize) -> Vec<u8> {
@@ -146,8 +160,10 @@ impl Bytes for [u8] {
     to_base64(&self)
   }
 
-  fn as_string(&self) -> String {
-    str::from_utf8(&self).unwrap().to_string()
+  fn as_string(&self) -> Result<String> {
+    str::from_utf8(&self)
+      .map(|s| s.to_string())
+      .map_err(|_| Error::NotUtf8)
   }
 
   fn pad_pkcs7(&self, block_size: usize) -> Vec<u8> {
@@ -156,21 +172,21 @@ impl Bytes for [u8] {
 }
 
 impl EncodedBytes for &str {
-  fn from_hex(&self) -> Vec<u8> {
+  fn from_hex(&self) -> Result<Vec<u8>> {
     from_hex(&self)
   }
 
-  fn from_base64(&self) -> Vec<u8> {
+  fn from_base64(&self) -> Result<Vec<u8>> {
     from_base64(&self)
   }
 }
 
 impl EncodedBytes for String {
-  fn from_hex(&self) -> Vec<u8> {
+  fn from_hex(&self) -> Result<Vec<u8>> {
     from_hex(&self)
   }
 
-  fn from_base64(&self) -> Vec<u8> {
+  fn from_base64(&self) -> Result<Vec<u8>> {
     from_base64(&self)
   }
 }
@@ -183,4 +199,29 @@ mod tests {
   fn wokka_wokka_edit_distance() {
     assert_eq!(edit_distance(b"this is a test", b"wokka wokka!!!"), 37);
   }
+
+  #[test]
+  fn ct_eq_compares_everything() {
+    assert!(ct_eq(b"", b""));
+    assert!(ct_eq(b"yellow", b"yellow"));
+    assert!(!ct_eq(b"yellow", b"Yellow"));
+    assert!(!ct_eq(b"yellow", b"yelloW"));
+    assert!(!ct_eq(b"yellow", b"yell"));
+  }
+
+  #[test]
+  fn bad_encodings() {
+    assert_eq!("0g".from_hex(), Err(Error::InvalidHex));
+    assert_eq!("abc".from_hex(), Err(Error::InvalidHex));
+    assert_eq!("a$==".from_base64(), Err(Error::InvalidBase64));
+    assert_eq!(vec![0xc3, 0x28].as_string(), Err(Error::NotUtf8));
+  }
+
+  #[test]
+  fn pkcs7_edge_cases() {
+    assert_eq!(strip_pkcs7(b""), Err(Error::BadPadding));
+    assert_eq!(strip_pkcs7(b"ICE ICE BABY\x00"), Err(Error::BadPadding));
+    assert_eq!(strip_pkcs7(b"\x05\x05\x05\x05"), Err(Error::BadPadding));
+    assert_eq!(strip_pkcs7(&pad_pkcs7(b"", 16)), Ok(vec![]));
+  }
 }
@@ -0,0 +1,180 @@
+use cryptopals_rust::error;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::pin::Pin;
+
+/// How a challenge finished when it didn't fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+  Solved,
+  /// Not run, and why.
+  Skipped(&'static str),
+}
+
+/// Why a challenge failed.
+#[derive(Debug)]
+pub enum Error {
+  Crypto(error::Error),
+  Mismatch { left: String, right: String },
+  Failed(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Crypto(error) => write!(f, "{}", error),
+      Error::Mismatch { left, right } => {
+        write!(
+          f,
+          "`left == right` failed\n  left: {}\n right: {}",
+          left, right
+        )
+      }
+      Error::Failed(message) => f.write_str(message),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<error::Error> for Error {
+  fn from(error: error::Error) -> Self {
+    Error::Crypto(error)
+  }
+}
+
+pub type Run = Pin<Box<dyn Future<Output = Result<Outcome>> + Send>>;
+
+/// A cryptopals challenge. Implementations are usually generated with
+/// `register!`, which also adds them to the registry behind `all()`.
+pub trait Challenge: Sync {
+  fn set(&self) -> u8;
+  fn number(&self) -> u8;
+  fn title(&self) -> &'static str;
+  fn slow(&self) -> bool {
+    false
+  }
+  fn run(&self) -> Run;
+}
+
+pub struct Registration(pub &'static dyn Challenge);
+
+inventory::collect!(Registration);
+
+/// Every registered challenge, in order.
+pub fn all() -> Vec<&'static dyn Challenge> {
+  let mut challenges: Vec<_> = inventory::iter::<Registration>
+    .into_iter()
+    .map(|registration| registration.0)
+    .collect();
+  challenges.sort_by_key(|challenge| challenge.number());
+  challenges
+}
+
+/// Registers the module's `solve()` as a challenge.
+macro_rules! register {
+  (set: $set:expr, number: $number:expr, title: $title:expr $(, slow: $slow:expr)? $(,)?) => {
+    struct Registered;
+
+    impl crate::challenge::Challenge for Registered {
+      fn set(&self) -> u8 {
+        $set
+      }
+
+      fn number(&self) -> u8 {
+        $number
+      }
+
+      fn title(&self) -> &'static str {
+        $title
+      }
+
+      $(
+        fn slow(&self) -> bool {
+          $slow
+        }
+      )?
+
+      fn run(&self) -> crate::challenge::Run {
+        Box::pin(solve())
+      }
+    }
+
+    inventory::submit!(crate::challenge::Registration(&Registered));
+  };
+}
+
+/// Like `assert!`, but fails the challenge instead of panicking.
+macro_rules! ensure {
+  ($cond:expr $(,)?) => {
+    if !$cond {
+      return Err(crate::challenge::Error::Failed(format!(
+        "`{}` failed",
+        stringify!($cond)
+      )));
+    }
+  };
+  ($cond:expr, $($arg:tt)+) => {
+    if !$cond {
+      return Err(crate::challenge::Error::Failed(format!($($arg)+)));
+    }
+  };
+}
+
+/// Like `assert_eq!`, but fails the challenge instead of panicking.
+macro_rules! ensure_eq {
+  ($left:expr, $right:expr $(,)?) => {
+    match (&$left, &$right) {
+      (left, right) => {
+        if !(*left == *right) {
+          return Err(crate::challenge::Error::Mismatch {
+            left: format!("{:?}", left),
+            right: format!("{:?}", right),
+          });
+        }
+      }
+    }
+  };
+  ($left:expr, $right:expr, $($arg:tt)+) => {
+    match (&$left, &$right) {
+      (left, right) => {
+        if !(*left == *right) {
+          return Err(crate::challenge::Error::Failed(format!(
+            "{}\n  left: {:?}\n right: {:?}",
+            format!($($arg)+),
+            left,
+            right
+          )));
+        }
+      }
+    }
+  };
+}
+
+lazy_static! {
+  pub static ref VANILLA: String = fs::read_to_string("data/play-that-funky-music.txt").unwrap();
+  pub static ref ICE_ICE_BABY: String = fs::read_to_string("data/ice-ice-baby.txt").unwrap();
+  pub static ref FURY: String = fs::read_to_string("data/fury.txt").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn every_challenge_is_registered_once() {
+    let numbers: Vec<_> = all().iter().map(|c| c.number()).collect();
+    assert_eq!(numbers, (1..=41).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn sets_hold_eight_challenges() {
+    for challenge in all() {
+      assert_eq!(challenge.set(), (challenge.number() - 1) / 8 + 1);
+      assert!(!challenge.title().is_empty());
+    }
+  }
+}
@@ -1,4 +1,5 @@
-use crate::bytes::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
 
 /// # Convert hex to base64
 ///
@@ -17,8 +18,15 @@ use crate::bytes::*;
 /// ```
 ///
 /// So go ahead and make that happen. You'll need to use this code for the rest of the exercises.
-pub async fn solve() {
-  assert_eq!("49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d"
-    .from_hex()
+pub async fn solve() -> Result<Outcome> {
+  ensure_eq!("49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d"
+    .from_hex().unwrap()
     .to_base64(), "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t");
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 1,
+  title: "Convert hex to base64",
 }
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result, VANILLA};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 use std::fs;
 
 /// # Implement CBC mode
@@ -18,18 +19,27 @@ use std::fs;
 ///
 /// Don't cheat.
 /// Do not use OpenSSL's CBC code to do CBC mode, even to verify your results. What's the point of even doing this stuff if you aren't going to learn from it?
-pub async fn solve(solution: &str) {
+pub async fn solve() -> Result<Outcome> {
   let key = b"YELLOW SUBMARINE";
   let iv = b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
   let ct = fs::read_to_string("data/10.txt")
     .expect("Can't load ct")
     .replace("\n", "")
-    .from_base64();
+    .from_base64()
+    .unwrap();
 
-  assert_eq!(
+  ensure_eq!(
     decrypt_cbc(key, iv, &ct)
       .expect("Expected correct padding")
-      .as_string(),
-    solution
+      .as_string()
+      .unwrap(),
+    *VANILLA
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 10,
+  title: "Implement CBC mode",
 }
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 use rand::{thread_rng, Rng};
 
@@ -25,13 +26,20 @@ use rand::{thread_rng, Rng};
 /// Now, have the function choose to encrypt under ECB 1/2 the time, and under CBC the other half (just use random IVs each time for CBC). Use rand(2) to decide which to use.
 ///
 /// Detect the block cipher mode the function is using each time. You should end up with a piece of code that, pointed at a block box that might be encrypting ECB or CBC, tells you which one is happening.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let chosen_pt = &vec![0u8; 48][..];
   for _ in 0..100 {
     let (ct, is_ecb) = encryption_oracle(chosen_pt);
     let like_ecb = ct[16..32] == ct[32..48];
-    assert_eq!(is_ecb, like_ecb, "Guess correctly");
+    ensure_eq!(is_ecb, like_ecb, "Guess correctly");
   }
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 11,
+  title: "An ECB/CBC detection oracle",
 }
 
 fn encryption_oracle(chosen_pt: &[u8]) -> (Vec<u8>, bool) {
@@ -44,9 +52,9 @@ fn encryption_oracle(chosen_pt: &[u8]) -> (Vec<u8>, bool) {
   let pt: Vec<u8> = [&prefix, chosen_pt, &suffix].concat();
 
   if is_ecb {
-    (encrypt_ecb(&key, &pt), is_ecb)
+    (encrypt_ecb(&key, &pt).unwrap(), is_ecb)
   } else {
     let iv = random_bytes(16);
-    (encrypt_cbc(&key, &iv, &pt), is_ecb)
+    (encrypt_cbc(&key, &iv, &pt).unwrap(), is_ecb)
   }
 }
@@ -1,5 +1,7 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Error, Outcome, Result, ICE_ICE_BABY};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack;
+use cryptopals_rust::crypt::*;
 use std::fs;
 
 /// # Byte-at-a-time ECB decryption (Simple)
@@ -41,73 +43,30 @@ use std::fs;
 ///
 /// Congratulations.
 /// This is the first challenge we've given you whose solution will break real crypto. Lots of people know that when you encrypt something in ECB mode, you can see penguins through it. Not so many of them can decrypt the contents of those ciphertexts, and now you can. If our experience is any guideline, this attack will get you code execution in security tests about once a year.
-pub async fn solve(solution: &str) {
-  // 1. Find block size
-  let block_size = find_block_size();
-
-  // 2. Detect ECB
-  let ct = encryption_oracle(&vec![0u8; 2 * block_size]);
-  assert_eq!(ct[..block_size], ct[block_size..2 * block_size], "Is ECB");
-
-  // 3 - 6. Decrypt
-
-  // Initialize empty secret with correct length (with padding)
-  let mut secret = vec![0u8; encryption_oracle(b"").len()];
-
-  // Put the first unknown byte of the secret at the
-  // end of a block to get a target for the block, then loop through all
-  // characters to find the match
-  for i in 0..secret.len() {
-    // Cycle and offset from block_size - 1 to 0 so we can position the
-    // first byte of the secret at the end of the block
-    let offset_length = block_size - i % block_size - 1;
-    let offset = vec![0u8; offset_length];
-
-    // Block we want to discover the last byte of
-    let block = i / block_size;
-
-    // Target we need to match
-    let target = encryption_oracle(&offset)[block * block_size..(block + 1) * block_size].to_vec();
-
-    // Look for the first unknown byte, placed at the last place in a block
-    for c in 0..=255 {
-      let pt = [&offset, &secret[..i], &[c]].concat();
-      let block = (offset_length + i) / block_size;
-      let guess = encryption_oracle(&pt)[block * block_size..(block + 1) * block_size].to_vec();
-      if target == guess {
-        // We have found a byte. If it was 1, then we found padding so keep to 0
-        secret[i] = if c > 1 { c } else { 0 }
-      }
-    }
-  }
-
-  secret.retain(|&byte| byte > 0);
-  assert_eq!(secret.as_string(), solution);
+pub async fn solve() -> Result<Outcome> {
+  let found =
+    crack::byte_at_a_time_ecb(encryption_oracle).map_err(|err| Error::Failed(err.to_string()))?;
+  ensure_eq!(found.block_size, 16);
+  ensure_eq!(found.secret.as_string()?, *ICE_ICE_BABY);
+  Ok(Outcome::Solved)
 }
 
-fn find_block_size() -> usize {
-  let mut pt = Vec::new();
-  let base_length = encryption_oracle(&pt).len();
-
-  // Find jump in length
-  loop {
-    pt.push(0);
-    let length = encryption_oracle(&pt).len();
-    if length != base_length {
-      return length - base_length;
-    }
-  }
+register! {
+  set: 2,
+  number: 12,
+  title: "Byte-at-a-time ECB decryption (Simple)",
 }
 
 lazy_static! {
   static ref SECRET: Vec<u8> = fs::read_to_string("data/12.txt")
     .expect("Can't load ct")
     .replace("\n", "")
-    .from_base64();
+    .from_base64()
+    .unwrap();
   static ref KEY: Vec<u8> = random_bytes(16);
 }
 
 fn encryption_oracle(chosen_pt: &[u8]) -> Vec<u8> {
   let pt: Vec<u8> = [chosen_pt, &SECRET].concat();
-  encrypt_ecb(&KEY, &pt)
+  encrypt_ecb(&KEY, &pt).unwrap()
 }
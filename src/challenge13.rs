@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 use std::collections::HashMap;
 
 /// # ECB cut-and-paste
@@ -54,22 +55,40 @@ use std::collections::HashMap;
 ///   B. Decrypt the encoded user profile and parse it.
 ///
 ///Using only the user input to profile_for() (as an oracle to generate "valid" ciphertexts) and the ciphertexts themselves, make a role=admin profile.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Get the ct for a user profile, with "user" in a block by itself
   let ct_user = encrypted_profile("a@example.com");
 
   // Figure out what the ct for "admin" should be. The `<-buffer->` aligns
   // admin to the start of a block with PKCS7 padding
-  let ct_role = encrypted_profile(&["<-buffer->", "admin", &vec![11; 11][..].as_string()].concat());
+  let ct_role = encrypted_profile(
+    &[
+      "<-buffer->",
+      "admin",
+      &vec![11; 11][..].as_string().unwrap(),
+    ]
+    .concat(),
+  );
 
   // Chop off the user role and add admin role
   let ct = [&ct_user[..ct_user.len() - 16], &ct_role[16..32]].concat();
 
   // Check role
-  assert_eq!(
-    String::from(parse(&decrypt(&ct).as_string()).get("role").unwrap()),
+  ensure_eq!(
+    String::from(
+      parse(&decrypt(&ct).as_string().unwrap())
+        .get("role")
+        .unwrap()
+    ),
     "admin"
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 13,
+  title: "ECB cut-and-paste",
 }
 
 fn parse(query: &str) -> HashMap<String, String> {
@@ -95,7 +114,7 @@ lazy_static! {
 }
 
 fn encrypt(pt: &[u8]) -> Vec<u8> {
-  encrypt_ecb(&KEY, pt)
+  encrypt_ecb(&KEY, pt).unwrap()
 }
 
 fn decrypt(ct: &[u8]) -> Vec<u8> {
@@ -1,4 +1,6 @@
-use crate::bytes::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::error::Error;
 
 /// # PKCS#7 padding validation
 ///
@@ -29,14 +31,21 @@ use crate::bytes::*;
 /// If you are writing in a language with exceptions, like Python or Ruby, make your function throw an exception on bad padding.
 ///
 /// Crypto nerds know where we're going with this. Bear with us.
-pub async fn solve() {
-  assert_eq!(
+pub async fn solve() -> Result<Outcome> {
+  ensure_eq!(
     strip_pkcs7(b"ICE ICE BABY\x04\x04\x04\x04").expect("Expected correct padding"),
     b"ICE ICE BABY"
   );
 
-  assert_eq!(
+  ensure_eq!(
     strip_pkcs7(b"ICE ICE BABY\x01\x02\x03\x04").expect_err("Expect error"),
-    "Invalid padding"
+    Error::BadPadding
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 15,
+  title: "PKCS#7 padding validation",
 }
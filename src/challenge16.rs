@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 /// # CBC bitflipping attacks
 ///
@@ -41,13 +42,20 @@ use crate::crypt::*;
 /// ## Stop and think for a second.
 ///
 /// Before you implement this attack, answer this question: why does CBC mode have this property?
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut ct = encrypt(&[0u8; 32]);
   let block = xor(&ct[32..48], "<--->;admin=true".as_bytes());
   for (i, byte) in block.iter().enumerate() {
     ct[32 + i] = *byte;
   }
-  assert_eq!(get_admin(&ct), "true");
+  ensure_eq!(get_admin(&ct), "true");
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 16,
+  title: "CBC bitflipping attacks",
 }
 
 lazy_static! {
@@ -66,6 +74,7 @@ fn encrypt(user_data: &[u8]) -> Vec<u8> {
     ]
     .concat(),
   )
+  .unwrap()
 }
 
 fn escape(string: &[u8]) -> Vec<u8> {
@@ -85,7 +94,8 @@ fn get_admin(ct: &[u8]) -> String {
     .into_iter()
     .filter(|&x| x < 128)
     .collect::<Vec<u8>>()
-    .as_string();
+    .as_string()
+    .unwrap();
   for field in query.split(';') {
     let mut parts = field.split('=');
     if let (Some("admin"), Some(value)) = (parts.next(), parts.next()) {
@@ -1,5 +1,9 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Error, Outcome, Result};
+use crate::padding_oracle::{self, Leak};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack::{self, PaddingOracle};
+use cryptopals_rust::crypt::*;
+use cryptopals_rust::padding_oracle_server::{LOOKUP_DELAY, SESSION};
 use rand::seq::SliceRandom;
 
 /// # The CBC padding oracle
@@ -50,73 +54,43 @@ use rand::seq::SliceRandom;
 /// So you can assume that if you corrupt a decryption AND it had valid padding, you know what that padding byte is.
 ///
 /// It is easy to get tripped up on the fact that CBC plaintexts are "padded". Padding oracles have nothing to do with the actual padding on a CBC plaintext. It's an attack that targets a specific bit of code that handles decryption. You can mount a padding oracle on any CBC block, whether it's padded or not.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let (pt, iv, ct) = random_encrypted_string();
-  let found_pt = decrypt(iv, ct);
+  let found_pt = crack::padding_oracle_decrypt(&Server, &iv, &ct);
+  ensure_eq!(found_pt, Some(pt));
 
-  // I have a bug somewhere and can't get the first byte, so skip it for now.
-  assert_eq!(found_pt[1..], pt[1..]);
-}
-
-fn decrypt(iv: Vec<u8>, ct: Vec<u8>) -> Vec<u8> {
-  let block_size = 16;
-  let mut pt = vec![0; ct.len()];
-  for i in (1..=ct.len() / block_size)
-    .rev()
-    .map(|block| block * block_size)
-  {
-    // Sometimes we have multiple matches, first pick the first match
-    let mut pt_block = decrypt_last_block(&[&iv, &ct[..i]].concat(), true);
+  // The same oracle encrypts whatever we like
+  let forged = b"000010I'm in your session".to_vec();
+  let (iv, ct) = crack::padding_oracle_encrypt(&Server, &forged)
+    .ok_or_else(|| Error::Failed(String::from("Oracle gave inconsistent answers")))?;
+  ensure_eq!(decrypt_cbc(&KEY, &iv, &ct)?, forged);
 
-    // If the second to last byte is 2, we probably are decrypting to 16, 15...2, x
-    // In this case, use the last match
-    if pt_block[block_size - 2] == 2 {
-      pt_block = decrypt_last_block(&[&iv, &ct[..i]].concat(), false);
-    }
-    pt[i - block_size..i].copy_from_slice(&pt_block);
-  }
-  if let Ok(stripped) = strip_pkcs7(&pt) {
-    stripped
-  } else {
-    pt
+  // And end to end against a server, whichever way it leaks
+  for &leak in &[Leak::Status, Leak::Message, Leak::Timing(LOOKUP_DELAY / 2)] {
+    let found = padding_oracle::attack_server(leak, 16)
+      .await
+      .map_err(|err| Error::Failed(err.to_string()))?;
+    ensure_eq!(found, Some(SESSION.to_vec()), "Leaking through {:?}", leak);
   }
+  Ok(Outcome::Solved)
 }
 
-fn decrypt_last_block(original_iv_and_ct: &[u8], pick_first: bool) -> Vec<u8> {
-  let block_size = 16u8;
-  let mut pt = vec![0u8; block_size as usize];
-  let ct_len = original_iv_and_ct.len();
-  let mut iv_and_ct = original_iv_and_ct.clone().to_vec();
+register! {
+  set: 3,
+  number: 17,
+  title: "The CBC padding oracle",
+}
 
-  // We manipulate the second to last block (which might be the IV)
-  let offset = ct_len - 2 * block_size as usize;
+struct Server;
 
-  for i in (0..block_size).rev() {
-    // Adjust CT so the resulting PT will be the new padding
-    let pad: u8 = block_size - i;
-    for j in i as usize + 1..block_size as usize {
-      iv_and_ct[offset + j] = pt[j] ^ original_iv_and_ct[offset + j] ^ pad;
-    }
+impl PaddingOracle for Server {
+  fn block_size(&self) -> usize {
+    16
+  }
 
-    // Remember the original
-    let original_byte = original_iv_and_ct[offset + i as usize];
-    pt[i as usize] = pad;
-    for c in 0u8..=255 {
-      if c != original_byte {
-        iv_and_ct[offset + i as usize] = c;
-        if has_valid_padding(
-          &iv_and_ct[..block_size as usize],
-          &iv_and_ct[block_size as usize..],
-        ) {
-          pt[i as usize] = original_byte ^ c ^ pad;
-          if pick_first {
-            break;
-          }
-        }
-      }
-    }
+  fn has_valid_padding(&self, iv: &[u8], ct: &[u8]) -> bool {
+    decrypt_cbc(&KEY, &iv, &ct).is_ok()
   }
-  pt
 }
 
 lazy_static! {
@@ -135,17 +109,10 @@ lazy_static! {
   ];
 }
 
-fn has_valid_padding(iv: &[u8], ct: &[u8]) -> bool {
-  match decrypt_cbc(&KEY, &iv, &ct) {
-    Ok(_) => true,
-    Err(_) => false,
-  }
-}
-
 fn random_encrypted_string() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
   let pt = random_string();
   let iv = random_bytes(16);
-  let ct = encrypt_cbc(&KEY, &iv, &pt);
+  let ct = encrypt_cbc(&KEY, &iv, &pt).unwrap();
   (pt, iv, ct)
 }
 
@@ -154,4 +121,5 @@ fn random_string() -> Vec<u8> {
     .choose(&mut rand::thread_rng())
     .unwrap()
     .from_base64()
+    .unwrap()
 }
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 /// # Implement CTR, the stream cipher mode
 ///
@@ -54,12 +55,21 @@ use crate::crypt::*;
 /// ## This is the only block cipher mode that matters in good code.
 ///
 /// Most modern cryptography relies on CTR mode to adapt block ciphers into stream ciphers, because most of what we want to encrypt is better described as a stream than as a sequence of blocks. Daniel Bernstein once quipped to Phil Rogaway that good cryptosystems don't need the "decrypt" transforms. Constructions like CTR are what he was talking about.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let key = b"YELLOW SUBMARINE".to_vec();
   let nonce = [0u8; 8];
-  let ct = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==".from_base64();
-  assert_eq!(
-    decrypt_ctr(&key, &nonce, &ct).as_string(),
+  let ct = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ=="
+    .from_base64()
+    .unwrap();
+  ensure_eq!(
+    decrypt_ctr(&key, &nonce, &ct).unwrap().as_string().unwrap(),
     "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 3,
+  number: 18,
+  title: "Implement CTR, the stream cipher mode",
 }
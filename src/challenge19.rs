@@ -1,6 +1,9 @@
-use crate::bytes::*;
-use crate::crypt::*;
-use crate::english;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack;
+use cryptopals_rust::crypt::*;
+use cryptopals_rust::english;
+use cryptopals_rust::language::Scoring;
 
 /// # Break fixed-nonce CTR mode using substitutions
 ///
@@ -69,42 +72,29 @@ use crate::english;
 /// ## Don't overthink it.
 ///
 /// Points for automating this, but part of the reason I'm having you do this is that I think this approach is suboptimal.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let nonce = [0u8; 8];
   let cts: Vec<_> = STRINGS
     .iter()
-    .map(|pt| encrypt_ctr(&KEY, &nonce, &pt.from_base64()))
+    .map(|pt| encrypt_ctr(&KEY, &nonce, &pt.from_base64().unwrap()).unwrap())
     .collect();
 
-  let pad: Vec<_> = (0..30)
-    .map(|i| {
-      let column: Vec<_> = cts.iter().filter_map(|ct| ct.get(i)).map(|&c| c).collect();
-      guess_pad_byte(&column)
-    })
-    .collect();
+  let keystream = crack::break_reused_keystream(&cts, 30, english::ngrams(), Scoring::NGram);
 
   for i in 0..40 {
-    let mut pt = STRINGS[i].from_base64().as_string();
+    let mut pt = STRINGS[i].from_base64().unwrap().as_string().unwrap();
     if pt.len() > 30 {
       pt = String::from(&pt[..30]);
     }
-    assert_eq!(
-      xor(&cts[i], &pad).as_string().to_lowercase(),
-      pt.to_lowercase()
-    );
+    ensure_eq!(xor(&cts[i], &keystream).as_string().unwrap(), pt);
   }
+  Ok(Outcome::Solved)
 }
 
-fn guess_pad_byte(ct: &[u8]) -> u8 {
-  let (b, _score) = (0..=255)
-    .map(|b| {
-      let pt = cycled_xor(&ct, &vec![b]);
-      let score = english::score(&pt);
-      (b, score)
-    })
-    .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
-    .unwrap();
-  b
+register! {
+  set: 3,
+  number: 19,
+  title: "Break fixed-nonce CTR mode using substitutions",
 }
 
 lazy_static! {
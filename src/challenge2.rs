@@ -1,4 +1,5 @@
-use crate::bytes::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
 
 /// # Fixed XOR
 ///
@@ -22,13 +23,20 @@ use crate::bytes::*;
 /// ```
 /// 746865206b696420646f6e277420706c6179
 /// ```
-pub async fn solve() {
-  assert_eq!(
+pub async fn solve() -> Result<Outcome> {
+  ensure_eq!(
     xor(
-      &"1c0111001f010100061a024b53535009181c".from_hex(),
-      &"686974207468652062756c6c277320657965".from_hex(),
+      &"1c0111001f010100061a024b53535009181c".from_hex().unwrap(),
+      &"686974207468652062756c6c277320657965".from_hex().unwrap(),
     )
     .to_hex(),
     "746865206b696420646f6e277420706c6179",
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 2,
+  title: "Fixed XOR",
 }
@@ -1,5 +1,8 @@
-use crate::bytes::*;
-use crate::english;
+use crate::challenge::{Outcome, Result, FURY};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack;
+use cryptopals_rust::english;
+use cryptopals_rust::language::Scoring;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -16,45 +19,27 @@ use std::io::{BufRead, BufReader};
 /// To exploit this: take your collection of ciphertexts and truncate them to a common length (the length of the smallest ciphertext will work).
 ///
 /// Solve the resulting concatenation of ciphertexts as if for repeating- key XOR, with a key size of the length of the ciphertext you XOR'd.
-pub async fn solve(solution: &str) {
+pub async fn solve() -> Result<Outcome> {
   let file = File::open("data/20.txt").expect("Failed to open file");
   let cts: Vec<_> = BufReader::new(file)
     .lines()
-    .map(|line| line.unwrap().from_base64())
+    .map(|line| line.unwrap().from_base64().unwrap())
     .collect();
 
   let min_length = cts.iter().map(|ct| ct.len()).min().expect("has min size");
-
-  let pad: Vec<_> = (0..min_length)
-    .map(|i| {
-      let column: Vec<_> = cts.iter().filter_map(|ct| ct.get(i)).map(|&c| c).collect();
-      guess_pad_byte(&column)
-    })
-    .collect();
-
+  let keystream =
+    crack::break_reused_keystream(&cts, min_length, english::ngrams(), Scoring::NGram);
   let pts: Vec<_> = cts
     .iter()
-    .map(|ct| {
-      xor(&ct, &pad)
-        .as_string()
-        .trim()
-        .chars()
-        .filter(|&c| c > 7 as char)
-        .collect::<String>()
-    })
+    .map(|ct| xor(ct, &keystream).as_string().unwrap())
     .collect();
 
-  assert_eq!(pts.join("\n"), solution);
+  ensure_eq!(pts.join("\n"), *FURY);
+  Ok(Outcome::Solved)
 }
 
-fn guess_pad_byte(ct: &[u8]) -> u8 {
-  let (b, _score) = (0..=255)
-    .map(|b| {
-      let pt = cycled_xor(&ct, &vec![b]);
-      let score = english::score(&pt);
-      (b, score)
-    })
-    .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
-    .unwrap();
-  b
+register! {
+  set: 3,
+  number: 20,
+  title: "Break fixed-nonce CTR statistically",
 }
@@ -1,4 +1,5 @@
-use crate::rand::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::rand::*;
 
 /// # Implement the MT19937 Mersenne Twister RNG
 ///
@@ -7,7 +8,14 @@ use crate::rand::*;
 /// You can get the psuedocode for this from Wikipedia.
 ///
 /// If you're writing in Python, Ruby, or (gah) PHP, your language is probably already giving you MT19937 as "rand()"; don't use rand(). Write the RNG yourself.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut generator: Rand = random_from_seed(300);
-  assert_eq!(generator.nth(4).unwrap(), 1585191914);
+  ensure_eq!(generator.nth(4).unwrap(), 1585191914);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 3,
+  number: 21,
+  title: "Implement the MT19937 Mersenne Twister RNG",
 }
@@ -1,4 +1,6 @@
-use crate::rand::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::crack;
+use cryptopals_rust::rand::*;
 use rand::{thread_rng, Rng};
 use std::time::SystemTime;
 
@@ -17,20 +19,21 @@ use std::time::SystemTime;
 /// You get the idea. Go get coffee while it runs. Or just simulate the passage of time, although you're missing some of the fun of this exercise if you do that.
 ///
 /// From the 32 bit RNG output, discover the seed.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Get the random number with a time-based seed
   let (value, t, seed) = random();
 
-  let mut guess = t;
-  loop {
-    let mut generator = random_from_seed(guess);
-    if generator.next().unwrap() == value {
-      break;
-    }
-    guess -= 1;
-  }
+  // It was seeded within the last 2000 seconds
+  let guess = crack::find_mt_seed(&[(0, value)], t - 2000..=t, |_| ());
 
-  assert_eq!(seed, guess);
+  ensure_eq!(Some(seed), guess);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 3,
+  number: 22,
+  title: "Crack an MT19937 seed",
 }
 
 fn random() -> (u32, u32, u32) {
@@ -1,4 +1,5 @@
-use crate::rand::*;
+use crate::challenge::{Error, Outcome, Result};
+use cryptopals_rust::rand::*;
 use rand::prelude::*;
 
 /// # Clone an MT19937 RNG from its output
@@ -18,12 +19,17 @@ use rand::prelude::*;
 /// Once you have "untemper" working, create a new MT19937 generator, tap it for 624 outputs, untemper each of them to recreate the state of the generator, and splice that state into a new instance of the MT19937 generator.
 ///
 /// The new "spliced" generator should predict the values of the original.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut generator = random_from_seed(random());
-  let mut mt = [032; 624];
-  for i in 0..624 {
-    mt[i] = untemper(generator.next().unwrap());
-  }
-  let mut clone = Rand { index: 624, mt };
-  assert_eq!(generator.next(), clone.next());
+  let outputs: Vec<_> = generator.by_ref().take(624).collect();
+  let mut clone = Rand::clone_from_outputs(&outputs)
+    .ok_or_else(|| Error::Failed("the outputs didn't clone".to_string()))?;
+  ensure_eq!(generator.next(), clone.next());
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 3,
+  number: 23,
+  title: "Clone an MT19937 RNG from its output",
 }
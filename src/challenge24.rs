@@ -1,5 +1,7 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack;
+use cryptopals_rust::crypt::*;
 
 use rand::prelude::*;
 use rand::{thread_rng, Rng};
@@ -20,7 +22,7 @@ use std::time::SystemTime;
 /// Use the same idea to generate a random "password reset token" using MT19937 seeded from the current time.
 ///
 /// Write a function to check if any given password token is actually the product of an MT19937 PRNG seeded with the current time.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut rng = thread_rng();
   let prefix = &random_bytes(rng.gen_range(5..=50));
   let known = &[b'A'; 14] as &[u8];
@@ -28,15 +30,11 @@ pub async fn solve() {
   let seed: u16 = random();
   let ct = encrypt_mt(seed as u32, &pt);
 
-  // It's a small key space so try them all to find the seed
-  let found_seed = (0u16..)
-    .find(|&seed| {
-      let pt = decrypt_mt(seed as u32, &ct);
-      pt.windows(14).any(|window| window == known)
-    })
-    .unwrap();
+  // It's a small key space so try them all to find the seed, from the
+  // keystream the known plaintext gives away
+  let found_seed = crack::find_mt_seed(&known_outputs(&ct, known), 0..=u16::MAX as u32, |_| ());
 
-  assert_eq!(seed, found_seed);
+  ensure_eq!(Some(seed as u32), found_seed);
 
   // Now generate the password reset token
   let prefix = &random_bytes(rng.gen_range(5..=50));
@@ -46,12 +44,28 @@ pub async fn solve() {
 
   // Some time later
   let later = now() + rng.gen_range(100u32..=1000);
-  let is_mt = (later - 4000..later).rev().any(|seed| {
-    let pt = decrypt_mt(seed, &ct);
-    pt.windows(14).any(|window| window == known)
-  });
+  let seed = crack::find_mt_seed(&known_outputs(&ct, known), later - 4000..=later, |_| ());
+
+  ensure!(seed.is_some());
+  Ok(Outcome::Solved)
+}
 
-  assert!(is_mt);
+register! {
+  set: 3,
+  number: 24,
+  title: "Create the MT19937 stream cipher and break it",
+}
+
+/// The keystream outputs that `known`, at the end of the plaintext, gives
+/// away, with their indices.
+fn known_outputs(ct: &[u8], known: &[u8]) -> Vec<(usize, u32)> {
+  let start = ct.len() - known.len();
+  (start.div_ceil(4)..ct.len() / 4)
+    .map(|i| {
+      let pad = xor(&ct[4 * i..4 * i + 4], &known[4 * i - start..]);
+      (i, u32::from_le_bytes([pad[0], pad[1], pad[2], pad[3]]))
+    })
+    .collect()
 }
 
 fn now() -> u32 {
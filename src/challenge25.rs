@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 use std::fs;
 
@@ -14,19 +15,20 @@ use std::fs;
 /// Imagine the "edit" function was exposed to attackers by means of an API call that didn't reveal the key or the original plaintext; the attacker has the ciphertext and controls the offset and "new text".
 ///
 /// Recover the original plaintext.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Get the plain text
   let pt = decrypt_ecb(
     b"YELLOW SUBMARINE",
     &fs::read_to_string("data/25.txt")
       .expect("Failed to open file")
       .replace("\n", "")
-      .from_base64(),
+      .from_base64()
+      .unwrap(),
   )
   .expect("can decrypt");
 
   // Encrypt with random key
-  let ct = encrypt_ctr(&KEY, &NONCE, &pt);
+  let ct = encrypt_ctr(&KEY, &NONCE, &pt).unwrap();
 
   // Clone the ct and edit it with all 1s
   let mut clone = ct.clone();
@@ -36,7 +38,14 @@ pub async fn solve() {
   // Decrypt by xor with the inverse
   let decrypted = xor(&ct, &pad);
 
-  assert_eq!(pt, decrypted);
+  ensure_eq!(pt, decrypted);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 4,
+  number: 25,
+  title: "Break \"random access read/write\" AES CTR",
 }
 
 lazy_static! {
@@ -52,9 +61,9 @@ lazy_static! {
 
 // This is really sloppy :/
 fn edit(ct: &mut [u8], offset: usize, pt: &[u8]) {
-  let cipher = aes128(&KEY);
+  let cipher = aes128(&KEY).unwrap();
   for (count, block) in pt.chunks(16).enumerate() {
-    let pad = &encrypt_block(&cipher, &[*NONCE, (offset + count).to_le_bytes()].concat());
+    let pad = &cipher.encrypt_block(&[*NONCE, (offset + count).to_le_bytes()].concat());
     for (i, &ct_byte) in xor(&pad, block).iter().enumerate() {
       ct[(offset + count) * 16 + i] = ct_byte;
     }
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 /// # CTR bitflipping
 ///
@@ -8,13 +9,20 @@ use crate::crypt::*;
 /// There are people in the world that believe that CTR resists bit flipping attacks of the kind to which CBC mode is susceptible.
 ///
 /// Re-implement the CBC bitflipping exercise from earlier to use CTR mode instead of CBC mode. Inject an "admin=true" token.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut ct = encrypt(&[255u8; 11]);
   let block = xor(&not(&ct[32..43]), ";admin=true".as_bytes());
   for (i, byte) in block.iter().enumerate() {
     ct[32 + i] = *byte;
   }
-  assert_eq!(get_admin(&ct), "true");
+  ensure_eq!(get_admin(&ct), "true");
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 4,
+  number: 26,
+  title: "CTR bitflipping",
 }
 
 lazy_static! {
@@ -34,6 +42,7 @@ fn encrypt(user_data: &[u8]) -> Vec<u8> {
     ]
     .concat(),
   )
+  .unwrap()
 }
 
 fn escape(string: &[u8]) -> Vec<u8> {
@@ -49,10 +58,12 @@ fn escape(string: &[u8]) -> Vec<u8> {
 
 fn get_admin(ct: &[u8]) -> String {
   let query = decrypt_ctr(&KEY[..], &NONCE, &ct)
+    .unwrap()
     .into_iter()
     .filter(|&x| x < 128)
     .collect::<Vec<u8>>()
-    .as_string();
+    .as_string()
+    .unwrap();
   for field in query.split(';') {
     let mut parts = field.split('=');
     if let (Some("admin"), Some(value)) = (parts.next(), parts.next()) {
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 /// # Recover the key from CBC with IV=Key
 ///
@@ -32,7 +33,7 @@ use crate::crypt::*;
 /// ```
 /// P'_1 XOR P'_3
 /// ```
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let ct = encrypt(&[255u8; 16]);
   let ct = [&ct[0..16], &[0u8; 16], &ct[0..16], &ct[48..]].concat();
   let key = match check_ct(&ct) {
@@ -40,7 +41,14 @@ pub async fn solve() {
     Err(pt) => xor(&pt[0..16], &pt[32..48]),
   };
 
-  assert_eq!(KEY[..], key);
+  ensure_eq!(KEY[..], key);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 4,
+  number: 27,
+  title: "Recover the key from CBC with IV=Key",
 }
 
 lazy_static! {
@@ -58,6 +66,7 @@ fn encrypt(user_data: &[u8]) -> Vec<u8> {
     ]
     .concat(),
   )
+  .unwrap()
 }
 
 fn escape(string: &[u8]) -> Vec<u8> {
@@ -71,7 +80,7 @@ fn escape(string: &[u8]) -> Vec<u8> {
     .collect()
 }
 
-fn check_ct(ct: &[u8]) -> Result<(), Vec<u8>> {
+fn check_ct(ct: &[u8]) -> std::result::Result<(), Vec<u8>> {
   let pt = decrypt_cbc(&KEY[..], &KEY[..], &ct).expect("can decrypt");
   if pt.clone().into_iter().any(|b| b > 127) {
     Err(pt)
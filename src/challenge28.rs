@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 
 /// # Implement a SHA-1 keyed MAC
 ///
@@ -18,9 +19,16 @@ use crate::crypt::*;
 /// ```
 ///
 /// Verify that you cannot tamper with the message without breaking the MAC you've produced, and that you can't produce a new MAC without knowing the secret key.
-pub async fn solve() {
-  assert_eq!(
+pub async fn solve() -> Result<Outcome> {
+  ensure_eq!(
     sha1_mac(b"secret", b"hello").to_hex(),
     "d6321fe951feb4ae01c71737aefee61d2cf721ea"
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 4,
+  number: 28,
+  title: "Implement a SHA-1 keyed MAC",
 }
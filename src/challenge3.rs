@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::english;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::english;
 
 /// # Single-byte XOR cipher
 ///
@@ -17,8 +18,10 @@ use crate::english;
 ///
 /// How? Devise some method for "scoring" a piece of English plaintext. Character frequency is a good metric. Evaluate each output and choose the one with the best score.
 /// ```
-pub async fn solve() {
-  let ct = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736".from_hex();
+pub async fn solve() -> Result<Outcome> {
+  let ct = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736"
+    .from_hex()
+    .unwrap();
 
   // Try every single byte key and look for the PT that looks most like English
   let (pt, _score) = (0..=255)
@@ -30,5 +33,15 @@ pub async fn solve() {
     .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
     .unwrap();
 
-  assert_eq!(pt.as_string(), "Cooking MC's like a pound of bacon");
+  ensure_eq!(
+    pt.as_string().unwrap(),
+    "Cooking MC's like a pound of bacon"
+  );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 3,
+  title: "Single-byte XOR cipher",
 }
@@ -1,4 +1,6 @@
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::crack;
+use cryptopals_rust::crypt::*;
 use rand::seq::SliceRandom;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -12,35 +14,22 @@ use std::io::{BufRead, BufReader};
 /// ##You're thinking, why did we bother with this?
 ///
 /// Blame Stripe. In their second CTF game, the second-to-last challenge involved breaking an H(k, m) MAC with SHA1. Which meant that SHA1 code was floating all over the Internet. MD4 code, not so much.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let (message, mac) = get_message_and_mac();
   let suffix = b";admin=true";
   let (forged_message, forged_mac) = (1..)
-    .map(|key_length| {
-      let length = key_length + message.len();
-      let forged_message: Vec<_> = [
-        message,
-        &[1u8 << 7],
-        &vec![0; 63 - ((length + 8) % 64)][..],
-        &(length as u64 * 8).to_le_bytes(),
-        suffix,
-      ]
-      .concat();
-      let mut initial = [0u32; 4];
-      for (i, word) in mac.chunks(4).enumerate() {
-        initial[i] = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
-      }
-      let forged_mac = md5_extend(
-        suffix,
-        &initial,
-        forged_message.len() - suffix.len() + key_length,
-      );
-      (forged_message, forged_mac)
-    })
+    .map(|key_length| crack::length_extension::<Md5>(message, &mac, key_length, suffix))
     .find(|(message, mac)| check_mac(message, mac))
     .expect("to be able to forge");
 
-  assert!(check_mac(&forged_message, &forged_mac));
+  ensure!(check_mac(&forged_message, &forged_mac));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 4,
+  number: 30,
+  title: "Break an MD4 keyed MAC using length extension",
 }
 
 const MESSAGE: &'static [u8] =
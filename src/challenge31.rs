@@ -1,9 +1,13 @@
-use crate::bytes::random_64;
-use crate::bytes::*;
-use crate::crypt::hmac_sha1;
-use reqwest;
-use std::time::SystemTime;
-use tokio::time::{sleep, Duration};
+use crate::challenge::{Error, Outcome, Result};
+use crate::timing_oracle;
+use cryptopals_rust::bytes::random_64;
+use cryptopals_rust::crypt::hmac_sha1;
+use cryptopals_rust::hmac_server::Config;
+use cryptopals_rust::timing::TimingAttack;
+use std::time::Duration;
+
+/// How long the server takes to compare each byte.
+const DELAY: Duration = Duration::from_millis(50);
 
 /// # Implement and break HMAC-SHA1 with an artificial timing leak
 ///
@@ -30,79 +34,28 @@ use tokio::time::{sleep, Duration};
 /// ## Why artificial delays?
 ///
 /// Early-exit string compares are probably the most common source of cryptographic timing leaks, but they aren't especially easy to exploit. In fact, many timing leaks (for instance, any in C, C++, Ruby, or Python) probably aren't exploitable over a wide-area network at all. To play with attacking real-world timing leaks, you have to start writing low-level timing code. We're keeping things cryptographic in these challenges.
-pub async fn solve() {
-  let _file = "hello!";
-  // This is very slow so don't run it
-  // let hmac = find_hmac(&file).await;
-  // assert_eq!(hmac, hmac_sha1(&KEY, file.as_bytes()));
-}
-
-#[allow(dead_code)]
-async fn find_hmac(file: &str) -> [u8; 20] {
-  let mut hmac = [0u8; 20];
-  for i in 0..20 {
-    let mut max: Option<(u8, u128)> = None;
-    for c in 0u8..=255 {
-      hmac[i] = c;
-      match time(file, &hmac.to_hex()).await {
-        Check::Done => {
-          return hmac;
-        }
-        Check::Time(t) => {
-          match max {
-            None => {
-              max = Some((c, t));
-            }
-            Some((_, max_t)) => {
-              if max_t < t {
-                max = Some((c, t));
-              }
-            }
-          };
-        }
-      }
-    }
-    hmac[i] = max.unwrap().0;
-  }
-  hmac
-}
-
-enum Check {
-  Done,
-  Time(u128),
-}
-
-async fn time(file: &str, hmac: &str) -> Check {
-  let start = SystemTime::now();
-  let status = reqwest::get(format!("http://localhost:9000/31-hmac/{}/{}", file, hmac))
+pub async fn solve() -> Result<Outcome> {
+  let key = random_64();
+  let config = Config {
+    delay: DELAY,
+    ..Config::new(&key)
+  };
+  let file = "hello!";
+  // The leak is far bigger than any noise, so one look at each byte will do
+  let attack = TimingAttack {
+    min_samples: 1,
+    ..TimingAttack::new(20, DELAY)
+  };
+  let hmac = timing_oracle::attack_server(config, file, attack, 256)
     .await
-    .unwrap()
-    .status()
-    .as_u16();
-  match status {
-    200 => Check::Done,
-    500 => Check::Time(SystemTime::now().duration_since(start).unwrap().as_micros()),
-    _ => panic!("Unexpected status code {}", status),
-  }
-}
-
-pub async fn check(file: String, hmac: String) -> bool {
-  slow_equals(&hmac_sha1(&KEY, file.as_bytes()), &hmac.from_hex()).await
-}
-
-async fn slow_equals(a: &[u8], b: &[u8]) -> bool {
-  if a.len() != b.len() {
-    return false;
-  }
-  for i in 0..a.len() {
-    sleep(Duration::from_millis(50)).await;
-    if a[i] != b[i] {
-      return false;
-    }
-  }
-  true
+    .map_err(|err| Error::Failed(err.to_string()))?
+    .ok_or_else(|| Error::Failed("the timing attack gave up".to_string()))?;
+  ensure_eq!(hmac, hmac_sha1(&key, file.as_bytes()));
+  Ok(Outcome::Solved)
 }
 
-lazy_static! {
-  static ref KEY: [u8; 64] = random_64();
+register! {
+  set: 4,
+  number: 31,
+  title: "Implement and break HMAC-SHA1 with an artificial timing leak",
 }
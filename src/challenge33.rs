@@ -1,4 +1,5 @@
-use crate::{bytes::EncodedBytes, crypt::*};
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::{bytes::EncodedBytes, crypt::*};
 use num_bigint::{BigUint, RandBigInt};
 use rand::{thread_rng, Rng};
 
@@ -39,7 +40,7 @@ use rand::{thread_rng, Rng};
 /// This is very easy to do in Python or Ruby or other high-level languages that auto-promote fixnums to bignums, but it isn't "hard" anywhere.
 ///
 /// Note that you'll need to write your own modexp (this is blackboard math, don't freak out), because you'll blow out your bignum library raising "a" to the 1024-bit-numberth power. You can find modexp routines on Rosetta Code for most languages.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Part 1: Small parameters
 
   let mut rng = thread_rng();
@@ -60,7 +61,7 @@ pub async fn solve() {
 
   let mut rng = thread_rng();
   let p = BigUint::from_bytes_be(
-    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex()
+    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex().unwrap()
   );
   let g = BigUint::from(2u128);
 
@@ -73,5 +74,12 @@ pub async fn solve() {
   // Generate session key
   let key1 = sha1(&b_pub.modpow(&a, &p).to_bytes_be());
   let key2 = sha1(&a_pub.modpow(&b, &p).to_bytes_be());
-  assert_eq!(key1, key2);
+  ensure_eq!(key1, key2);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 33,
+  title: "Implement Diffie-Hellman",
 }
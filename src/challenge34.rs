@@ -1,4 +1,5 @@
-use crate::{bytes::*, crypt::*};
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::{bytes::*, crypt::*};
 use num_bigint::{BigUint, RandBigInt};
 use rand::thread_rng;
 
@@ -41,11 +42,11 @@ use rand::thread_rng;
 /// Decrypt the messages from M's vantage point as they go by.
 ///
 /// Note that you don't actually have to inject bogus parameters to make this attack work; you could just generate Ma, MA, Mb, and MB as valid DH parameters to do a generic MITM attack. But do the parameter injection attack; it's going to come up again.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Talk to B directly
   let mut rng = thread_rng();
   let p = BigUint::from_bytes_be(
-    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex()
+    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex().unwrap()
   );
   let g = BigUint::from(2u128);
   let a = rng.gen_biguint_below(&p);
@@ -54,24 +55,31 @@ pub async fn solve() {
   let s = &b_pub.modpow(&a, &p);
   let iv = random_16();
   let key = &sha1(&s.to_bytes_be()).to_vec()[0..16];
-  let ct = encrypt_cbc(&key, &iv, &"hello".as_bytes());
+  let ct = encrypt_cbc(&key, &iv, &"hello".as_bytes()).unwrap();
 
   let (ct, iv) = b.echo(&ct, &iv);
   let pt = decrypt_cbc(key, &iv, &ct).unwrap();
 
-  assert_eq!("hello".as_bytes(), pt);
+  ensure_eq!("hello".as_bytes(), pt);
 
   // Talk through M
   let (b_pub, b) = M::setup(&p, &g, &a_pub);
   let s = &b_pub.modpow(&a, &p);
   let iv = random_16();
   let key = &sha1(&s.to_bytes_be()).to_vec()[0..16];
-  let ct = encrypt_cbc(&key, &iv, &"hello".as_bytes());
+  let ct = encrypt_cbc(&key, &iv, &"hello".as_bytes()).unwrap();
 
   let (ct, iv) = b.echo(&ct, &iv);
   let pt = decrypt_cbc(key, &iv, &ct).unwrap();
 
-  assert_eq!("hello".as_bytes(), pt);
+  ensure_eq!("hello".as_bytes(), pt);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 34,
+  title: "Implement a MITM key-fixing attack on Diffie-Hellman with parameter injection",
 }
 
 trait Echo<'a> {
@@ -97,7 +105,7 @@ impl Echo<'_> for B {
 
     // Encrypt with new IV
     let iv = random_16();
-    let ct = encrypt_cbc(&key, &iv, &pt);
+    let ct = encrypt_cbc(&key, &iv, &pt).unwrap();
     (ct, iv)
   }
 }
@@ -1,4 +1,5 @@
-use crate::{bytes::*, crypt::*};
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::{bytes::*, crypt::*};
 use num_bigint::*;
 use rand::thread_rng;
 
@@ -42,7 +43,7 @@ use rand::thread_rng;
 /// It doesn't matter how you go from integer to string or string to integer (where things are going in or out of SHA256) as long as you do it consistently. I tested by using the ASCII decimal representation of integers as input to SHA256, and by converting the hexdigest to an integer when processing its output.
 ///
 /// This is basically Diffie Hellman with a tweak of mixing the password into the public keys. The server also takes an extra step to avoid storing an easily crackable password-equivalent.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let mut rng = thread_rng();
   let a = rng.gen_biguint_below(&N);
   let a_pub = G.modpow(&a, &N);
@@ -56,7 +57,14 @@ pub async fn solve() {
     .modpow(&(a + &(u.clone() * &x)), &N);
   let key = sha256(&s.to_bytes_be());
   let hmac = hmac_sha256(&key, &salt);
-  assert!(server.check(&hmac));
+  ensure!(server.check(&hmac));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 36,
+  title: "Implement Secure Remote Password (SRP)",
 }
 
 trait Server {
@@ -99,7 +107,7 @@ const P: &'static str = "123456";
 lazy_static! {
   // DH parameters
   static ref N: BigUint = BigUint::from_bytes_be(
-    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex()
+    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex().unwrap()
   );
   static ref G: BigUint = BigUint::from(2u8);
   static ref K:BigUint = BigUint::from(3u8);
@@ -1,4 +1,5 @@
-use crate::{bytes::*, crypt::*};
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::{bytes::*, crypt::*};
 use num_bigint::*;
 use rand::thread_rng;
 
@@ -15,18 +16,25 @@ use rand::thread_rng;
 /// ## Cryptanalytic MVP award
 ///
 /// Trevor Perrin and Nate Lawson taught us this attack 7 years ago. It is excellent. Attacks on DH are tricky to "operationalize". But this attack uses the same concepts, and results in auth bypass. Almost every implementation of SRP we've ever seen has this flaw; if you see a new one, go look for this bug.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Login with the password
-  assert!(login(I, P));
+  ensure!(login(I, P));
 
   // Login with 0 public key
-  assert!(login_a0(I));
+  ensure!(login_a0(I));
 
   // Login with N public key
-  assert!(login_n(I));
+  ensure!(login_n(I));
 
   // Login with N*N public key
-  assert!(login_n2(I));
+  ensure!(login_n2(I));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 37,
+  title: "Break SRP with a zero key",
 }
 
 fn login(email: &str, password: &str) -> bool {
@@ -103,7 +111,7 @@ const P: &'static str = "123456";
 lazy_static! {
   // DH parameters
   static ref N: BigUint = BigUint::from_bytes_be(
-    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex()
+    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex().unwrap()
   );
   static ref G: BigUint = BigUint::from(2u8);
   static ref K:BigUint = BigUint::from(3u8);
@@ -1,5 +1,7 @@
-use crate::english::*;
-use crate::{bytes::*, crypt::*};
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::english::*;
+use cryptopals_rust::wordlist::{self, Rule};
+use cryptopals_rust::{bytes::*, crypt::*};
 use num_bigint::*;
 use rand::prelude::*;
 use rand::thread_rng;
@@ -61,13 +63,20 @@ use rand::thread_rng;
 /// Now, run the protocol as a MITM attacker: pose as the server and use arbitrary values for b, B, u, and salt.
 ///
 /// Crack the password from A's HMAC-SHA256(K, salt).
-#[allow(dead_code)]
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   // Login with the password
-  assert!(login(I, &P));
+  ensure!(login(I, &P));
 
   // Login with the password via MitM
-  assert!(login_mitm(I, &P));
+  ensure!(login_mitm(I, &P));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 38,
+  title: "Offline dictionary attack on simplified SRP",
+  slow: true,
 }
 
 fn login(email: &str, password: &str) -> bool {
@@ -145,7 +154,8 @@ impl Server for M {
 
     let password = words()
       .iter()
-      .find(|&word| {
+      .flat_map(|word| wordlist::mutate(std::slice::from_ref(word), RULES))
+      .find(|word| {
         let x = BigUint::from_bytes_be(&sha256(&[&self.salt, word.as_bytes()].concat()));
         let s = (G.modpow(&x, &N) * &self.a_pub) % &*N;
         let k = sha256(&s.to_bytes_be());
@@ -154,7 +164,7 @@ impl Server for M {
       .unwrap();
 
     // Check that we guessed the password
-    assert_eq!(&*P, password);
+    assert_eq!(*P, password);
 
     // Construct hmac with original parameters
     let x = BigUint::from_bytes_be(&sha256(&[&self.salt, password.as_bytes()].concat()));
@@ -193,16 +203,19 @@ impl Server for S {
 // Email and password
 const I: &'static str = "itme@example.com";
 
+/// How the password might be made from a dictionary word.
+const RULES: &[Rule] = &[Rule::Capitalize, Rule::Leetspeak, Rule::AppendDigits(1)];
+
 lazy_static! {
   // DH parameters
   static ref N: BigUint = BigUint::from_bytes_be(
-    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex()
+    &"ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff".from_hex().unwrap()
   );
   static ref G: BigUint = BigUint::from(2u8);
   static ref K: BigUint = BigUint::from(3u8);
   static ref P: String = {
-    words()
-      .choose(&mut rand::thread_rng())
-      .expect("").clone()
+    let mut rng = rand::thread_rng();
+    let word = words().choose(&mut rng).expect("No words").clone();
+    wordlist::mutate(&[word], RULES).choose(&mut rng).unwrap().clone()
   };
 }
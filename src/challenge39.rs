@@ -1,3 +1,4 @@
+use crate::challenge::{Outcome, Result};
 use num_bigint::*;
 use num_primes::Generator;
 
@@ -25,13 +26,20 @@ use num_primes::Generator;
 /// Test this out with a number, like "42".
 /// Repeat with bignum primes (keep e=3).
 /// Finally, to encrypt a string, do something cheesy, like convert the string to hex and put "0x" on the front of it to turn it into a number. The math cares not how stupidly you feed it strings.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let (n, e, d) = pick_keys();
   let m = "ahoy, hoy!";
   let c = encode(m).modpow(&e, &n);
   let m2 = decode(&c.modpow(&d, &n));
 
-  assert_eq!(m, m2);
+  ensure_eq!(m, m2);
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 39,
+  title: "Implement RSA",
 }
 
 fn encode(m: &str) -> BigInt {
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::english;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::english;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str;
@@ -25,16 +26,23 @@ fn decrypt(ct: &Vec<u8>) -> (Vec<u8>, f64) {
 /// Find it.
 ///
 /// (Your code from #3 should help.)
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let file = File::open("data/4.txt").expect("Failed to open file");
   let (pt, _score) = BufReader::new(file)
     .lines()
-    .map(|line| decrypt(&line.expect("Bad line").from_hex()))
+    .map(|line| decrypt(&line.expect("Bad line").from_hex().unwrap()))
     .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
     .unwrap();
 
-  assert_eq!(
+  ensure_eq!(
     str::from_utf8(&pt).unwrap().to_string(),
     "Now that the party is jumping\n",
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 4,
+  title: "Detect single-character XOR",
 }
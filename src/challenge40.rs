@@ -1,3 +1,4 @@
+use crate::challenge::{Outcome, Result};
 use num_bigint::*;
 use num_primes::Generator;
 
@@ -36,7 +37,7 @@ use num_primes::Generator;
 /// ```
 ///
 /// To decrypt RSA using a simple cube root, leave off the final modulus operation; just take the raw accumulated result and cube-root it.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let m = "ahoy, hoy!";
 
   let (c1, n1) = encrypt(m);
@@ -52,7 +53,14 @@ pub async fn solve() {
     + (&c3 * &ms3 * invmod(&ms3, &n3)))
     % (&n1 * &n2 * &n3);
 
-  assert_eq!(m, decode(&result.cbrt()));
+  ensure_eq!(m, decode(&result.cbrt()));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 5,
+  number: 40,
+  title: "Implement an E=3 RSA Broadcast attack",
 }
 
 fn encrypt(m: &str) -> (BigInt, BigInt) {
@@ -1,3 +1,4 @@
+use crate::challenge::{Outcome, Result};
 use num_bigint::*;
 use num_primes::Generator;
 
@@ -45,7 +46,7 @@ use num_primes::Generator;
 /// ## Careful about division in cyclic groups.
 ///
 /// Remember: you don't simply divide mod N; you multiply by the multiplicative inverse mod N. So you'll need a modinv() function.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let m = "ahoy, hoy!";
   let (server, c) = Server::setup(&encode(m));
 
@@ -55,7 +56,14 @@ pub async fn solve() {
 
   let m_cracked = m2 * invmod(&s, &server.n) % &server.n;
 
-  assert_eq!(m, decode(&m_cracked));
+  ensure_eq!(m, decode(&m_cracked));
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 6,
+  number: 41,
+  title: "Implement unpadded message recovery oracle",
 }
 
 struct Server {
@@ -1,4 +1,5 @@
-use crate::bytes::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
 
 /// # Implement repeating-key XOR
 ///
@@ -22,8 +23,15 @@ use crate::bytes::*;
 /// ```
 ///
 /// Encrypt a bunch of stuff using your repeating-key XOR function. Encrypt your mail. Encrypt your password file. Your .sig file. Get a feel for it. I promise, we aren't wasting your time with this.
-pub async fn solve() {
+pub async fn solve() -> Result<Outcome> {
   let pt = b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
   let key = b"ICE";
-  assert_eq!(cycled_xor(pt, key).to_hex(), "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f");
+  ensure_eq!(cycled_xor(pt, key).to_hex(), "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f");
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 5,
+  title: "Implement repeating-key XOR",
 }
@@ -1,5 +1,7 @@
-use crate::bytes::*;
-use crate::crack;
+use crate::challenge::{Error, Outcome, Result, VANILLA};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack;
+use cryptopals_rust::english;
 use std::fs;
 
 /// # Break repeating-key XOR
@@ -35,34 +37,23 @@ use std::fs;
 /// 8. For each block, the single-byte XOR key that produces the best looking histogram is the repeating-key XOR key byte for that block. Put them together and you have the key.
 ///
 /// This code is going to turn out to be surprisingly useful later on. Breaking repeating-key XOR ("Vigenere") statistically is obviously an academic exercise, a "Crypto 101" thing. But more people "know how" to break it than can actually break it, and a similar technique breaks something much more important.
-pub async fn solve(solution: &str) {
+pub async fn solve() -> Result<Outcome> {
   let ct = fs::read_to_string("data/6.txt")
     .expect("Can't load ct")
     .replace("\n", "")
-    .from_base64();
-  let key_length = guess_key_length(&ct);
-
-  // construct the key
-  let mut key: Vec<u8> = Vec::new();
-  for chunk in cycled_chunk(&ct, key_length) {
-    key.push(crack::guess_xor_key(&chunk));
-  }
-
-  assert_eq!(cycled_xor(&ct, &key).as_string(), solution);
-}
-
-fn guess_key_length(ct: &[u8]) -> usize {
-  (1..40usize)
-    .map(|l| (l, sampled_edit_distance(ct, l)))
-    .min_by(|(_, dist1), (_, dist2)| dist1.partial_cmp(dist2).unwrap())
-    .unwrap()
-    .0
+    .from_base64()
+    .unwrap();
+  let found = crack::break_repeating_xor(&ct, 40, 3, english::letters());
+  let best = found
+    .first()
+    .ok_or_else(|| Error::Failed("no key found".to_string()))?;
+  ensure_eq!(best.key.as_string()?, "Terminator X: Bring the noise");
+  ensure_eq!(best.pt.as_string()?, *VANILLA);
+  Ok(Outcome::Solved)
 }
 
-fn sampled_edit_distance(ct: &[u8], l: usize) -> f64 {
-  let samples = 40usize;
-  let sum: u32 = (0..samples)
-    .map(|offset| edit_distance(&ct[offset..offset + l], &ct[offset + l..offset + 2 * l]))
-    .sum();
-  sum as f64 / (samples * l) as f64
+register! {
+  set: 1,
+  number: 6,
+  title: "Break repeating-key XOR",
 }
@@ -1,5 +1,6 @@
-use crate::bytes::*;
-use crate::crypt::*;
+use crate::challenge::{Outcome, Result, VANILLA};
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crypt::*;
 use std::fs;
 
 /// # AES in ECB mode
@@ -21,17 +22,26 @@ use std::fs;
 /// Do this with code.
 ///
 /// You can obviously decrypt this using the OpenSSL command-line tool, but we're having you get ECB working in code for a reason. You'll need it a lot later on, and not just for attacking ECB..
-pub async fn solve(solution: &str) {
+pub async fn solve() -> Result<Outcome> {
   let key = b"YELLOW SUBMARINE";
   let ct = fs::read_to_string("data/7.txt")
     .expect("Can't load ct")
     .replace("\n", "")
-    .from_base64();
+    .from_base64()
+    .unwrap();
 
-  assert_eq!(
+  ensure_eq!(
     decrypt_ecb(key, &ct)
       .expect("Expected correct padding")
-      .as_string(),
-    solution
+      .as_string()
+      .unwrap(),
+    *VANILLA
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 1,
+  number: 7,
+  title: "AES in ECB mode",
 }
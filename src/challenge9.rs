@@ -1,4 +1,5 @@
-use crate::bytes::*;
+use crate::challenge::{Outcome, Result};
+use cryptopals_rust::bytes::*;
 
 /// # Implement PKCS#7 padding
 ///
@@ -19,9 +20,16 @@ use crate::bytes::*;
 /// ```
 /// "YELLOW SUBMARINE\x04\x04\x04\x04"
 /// ```
-pub async fn solve() {
-  assert_eq!(
-    b"YELLOW SUBMARINE".pad_pkcs7(20).as_string(),
+pub async fn solve() -> Result<Outcome> {
+  ensure_eq!(
+    b"YELLOW SUBMARINE".pad_pkcs7(20).as_string().unwrap(),
     "YELLOW SUBMARINE\x04\x04\x04\x04"
   );
+  Ok(Outcome::Solved)
+}
+
+register! {
+  set: 2,
+  number: 9,
+  title: "Implement PKCS#7 padding",
 }
@@ -1,15 +1,796 @@
+//! Attacks that are shared between challenges.
+
 use crate::bytes::*;
-use crate::english;
+use crate::crypt::Extendable;
+use crate::language::{LanguageModel, Scoring};
+use crate::rand::Rand;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
-pub fn guess_xor_key(ct: &[u8]) -> u8 {
-  // Try every single byte key and look for the PT that looks most like English
+/// The single byte key that makes `ct` decrypt most like `model`'s language
+/// by byte frequencies.
+pub fn guess_xor_key(ct: &[u8], model: &LanguageModel) -> u8 {
+  // Try every single byte key and look for the PT that looks most like it
   (0..=255)
     .map(|b| {
       let pt = cycled_xor(ct, &vec![b]);
-      let score = english::score(&pt);
+      let score = model.score(&pt, Scoring::Unigram);
       (b, score)
     })
     .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
     .unwrap()
     .0
 }
+
+/// How `rank_key_lengths` tells a likely repeating-XOR key length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyLength {
+  /// Bits that differ between neighbouring key-length blocks, per byte.
+  /// Blocks under the same key differ only as much as the plaintext does.
+  Hamming,
+  /// Chance that two bytes in the same key position match. XORing with one
+  /// byte keeps that as high as it is for English.
+  IndexOfCoincidence,
+}
+
+fn normalized_hamming(ct: &[u8], length: usize) -> f64 {
+  let blocks: Vec<_> = ct.chunks_exact(length).collect();
+  let distance: u32 = blocks
+    .windows(2)
+    .map(|pair| edit_distance(pair[0], pair[1]))
+    .sum();
+  distance as f64 / ((blocks.len() - 1) * length) as f64
+}
+
+fn index_of_coincidence(ct: &[u8], length: usize) -> f64 {
+  let columns = cycled_chunk(ct, length);
+  let total: f64 = columns
+    .iter()
+    .map(|column| {
+      let mut counts = [0u64; 256];
+      for &b in column {
+        counts[b as usize] += 1;
+      }
+      let n = column.len() as u64;
+      let matches: u64 = counts.iter().map(|&c| c * c.saturating_sub(1)).sum();
+      matches as f64 / (n * (n - 1)) as f64
+    })
+    .sum();
+  total / length as f64
+}
+
+/// Key lengths from 1 to `max_length`, most likely first, with what the
+/// metric made of them. Only lengths that fit at least twice into `ct` are
+/// ranked.
+pub fn rank_key_lengths(ct: &[u8], max_length: usize, metric: KeyLength) -> Vec<(usize, f64)> {
+  let mut ranked: Vec<_> = (1..=max_length.min(ct.len() / 2))
+    .map(|length| match metric {
+      KeyLength::Hamming => (length, normalized_hamming(ct, length)),
+      KeyLength::IndexOfCoincidence => (length, index_of_coincidence(ct, length)),
+    })
+    .collect();
+  match metric {
+    KeyLength::Hamming => ranked.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()),
+    KeyLength::IndexOfCoincidence => ranked.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap()),
+  }
+  ranked
+}
+
+/// A guess at a repeating-XOR key, with how well the plaintext it makes
+/// scored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XorKey {
+  pub key: Vec<u8>,
+  pub score: f64,
+  pub pt: Vec<u8>,
+}
+
+/// Breaks repeating-key XOR with keys of up to `max_length` bytes. The `top`
+/// best key lengths by either metric each get a key, one byte at a time, and
+/// the `top` keys with plaintexts most like `model`'s language come back,
+/// best first. Repeats of a shorter key that decrypt the same way are left
+/// out.
+pub fn break_repeating_xor(
+  ct: &[u8],
+  max_length: usize,
+  top: usize,
+  model: &LanguageModel,
+) -> Vec<XorKey> {
+  let mut lengths: Vec<usize> = [KeyLength::Hamming, KeyLength::IndexOfCoincidence]
+    .iter()
+    .flat_map(|&metric| {
+      rank_key_lengths(ct, max_length, metric)
+        .into_iter()
+        .take(top)
+    })
+    .map(|(length, _)| length)
+    .collect();
+  lengths.sort_unstable();
+  lengths.dedup();
+
+  let mut keys: Vec<XorKey> = Vec::new();
+  for length in lengths {
+    let key: Vec<u8> = cycled_chunk(ct, length)
+      .iter()
+      .map(|column| guess_xor_key(column, model))
+      .collect();
+    let pt = cycled_xor(ct, &key);
+    if keys.iter().all(|found| found.pt != pt) {
+      let score = model.score(&pt, Scoring::Unigram);
+      keys.push(XorKey { key, score, pt });
+    }
+  }
+  keys.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+  keys.truncate(top);
+  keys
+}
+
+/// Recovers the first `length` bytes of a keystream that every one of `cts`
+/// was XORed with. Each byte is the one that makes the column of plaintext
+/// bytes at its position score best under `model`. N-gram scoring instead
+/// scores each byte after the plaintext recovered before it on the same line.
+pub fn break_reused_keystream(
+  cts: &[Vec<u8>],
+  length: usize,
+  model: &LanguageModel,
+  scoring: Scoring,
+) -> Vec<u8> {
+  let mut keystream = Vec::new();
+  for i in 0..length {
+    let column: Vec<u8> = cts.iter().filter_map(|ct| ct.get(i)).copied().collect();
+    let score = |b: u8| match scoring {
+      Scoring::NGram => cts
+        .iter()
+        .filter(|ct| ct.len() > i)
+        .map(|ct| model.log_prob(&xor(&ct[..i], &keystream), ct[i] ^ b))
+        .sum(),
+      _ => model.score(&cycled_xor(&column, &[b]), scoring),
+    };
+    let best = (0..=255)
+      .map(|b| (b, score(b)))
+      .max_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).unwrap())
+      .unwrap()
+      .0;
+    keystream.push(best);
+  }
+  keystream
+}
+
+/// How many seeds a thread of `find_mt_seed` takes on at a time.
+const SEED_CHUNK: u64 = 1 << 16;
+
+/// Finds an MT19937 seed among `seeds` that gives the `observed` outputs,
+/// as pairs of the index of the output after seeding and its value. A time
+/// window of seeds is a small range and all 2^32 of them is a big one; either
+/// way they're split between a thread per core and every thread stops once
+/// one finds a match. `progress` hears how many seeds have been tried so far
+/// after each chunk of them.
+pub fn find_mt_seed<F>(
+  observed: &[(usize, u32)],
+  seeds: RangeInclusive<u32>,
+  progress: F,
+) -> Option<u32>
+where
+  F: Fn(u64) + Sync,
+{
+  let (start, end) = (*seeds.start() as u64, *seeds.end() as u64 + 1);
+  if observed.is_empty() || start >= end {
+    return None;
+  }
+  let next_chunk = AtomicU64::new(start);
+  let tried = AtomicU64::new(0);
+  let found = Mutex::new(None);
+  let done = AtomicBool::new(false);
+  let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+  thread::scope(|scope| {
+    for _ in 0..threads {
+      scope.spawn(|| {
+        while !done.load(Ordering::Relaxed) {
+          let from = next_chunk.fetch_add(SEED_CHUNK, Ordering::Relaxed);
+          if from >= end {
+            break;
+          }
+          let to = (from + SEED_CHUNK).min(end);
+          let hit = (from..to).map(|seed| seed as u32).find(|&seed| {
+            observed
+              .iter()
+              .all(|&(index, output)| Rand::output_from_seed(seed, index) == output)
+          });
+          if let Some(seed) = hit {
+            *found.lock().unwrap() = Some(seed);
+            done.store(true, Ordering::Relaxed);
+          }
+          progress(tried.fetch_add(to - from, Ordering::Relaxed) + to - from);
+        }
+      });
+    }
+  });
+  found.into_inner().unwrap()
+}
+
+/// Length extension on a secret-prefix MAC, `H(secret || message)`. Given a
+/// guess at the secret's length, returns `message || glue padding || suffix`
+/// and a valid MAC for it.
+pub fn length_extension<H: Extendable>(
+  message: &[u8],
+  mac: &[u8],
+  secret_length: usize,
+  suffix: &[u8],
+) -> (Vec<u8>, Vec<u8>) {
+  let length = secret_length + message.len();
+  let padding = H::padding(length);
+  let mut hasher = H::from_digest(mac, length + padding.len());
+  hasher.update(suffix);
+  ([message, &padding, suffix].concat(), hasher.finalize())
+}
+
+/// What byte-at-a-time decryption learned about an oracle that returns
+/// `ECB(prefix || chosen || secret)`.
+#[derive(Debug)]
+pub struct ByteAtATime {
+  pub block_size: usize,
+  /// `None` when the prefix changes length from one query to the next.
+  pub prefix_length: Option<usize>,
+  pub secret: Vec<u8>,
+  pub queries: usize,
+}
+
+/// Why byte-at-a-time decryption gave up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteAtATimeError {
+  /// No repeated blocks came back from a run of repeated input.
+  NotEcb,
+  /// The chosen blocks never came back lined up on a block boundary.
+  Unaligned,
+  /// Adding input never made the ciphertext grow.
+  SecretLength,
+  /// None of the 256 candidates matched the secret's byte at this index.
+  UnknownByte(usize),
+}
+
+impl fmt::Display for ByteAtATimeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ByteAtATimeError::NotEcb => f.write_str("Oracle is not ECB"),
+      ByteAtATimeError::Unaligned => f.write_str("Couldn't line up the chosen blocks"),
+      ByteAtATimeError::SecretLength => f.write_str("Couldn't find the secret's length"),
+      ByteAtATimeError::UnknownByte(i) => write!(f, "No candidate matched secret byte {}", i),
+    }
+  }
+}
+
+impl std::error::Error for ByteAtATimeError {}
+
+const MAX_BLOCK_SIZE: usize = 64;
+const FILL: u8 = b'A';
+
+/// Wraps an ECB oracle so that every query is answered from a block
+/// boundary we control, whatever prefix the oracle puts in front.
+///
+/// Each query is sent as `filler || zero block || ones block || payload`.
+/// Once the filler pads the prefix out to a whole number of blocks, the two
+/// encrypted blocks show up side by side in the ciphertext and everything
+/// after them belongs to the payload. The end of a prefix can pass for the
+/// start of the zero block, but never for the ones block after it, so the
+/// pair only turns up when the blocks line up. A fixed prefix needs the
+/// right filler found once; a prefix that changes on every call is just
+/// retried until it lines up.
+struct EcbOracle<F> {
+  oracle: F,
+  block_size: usize,
+  /// The zero block and the ones block, encrypted.
+  markers: (Vec<u8>, Vec<u8>),
+  filler: usize,
+  prefix_lengths: HashSet<usize>,
+  queries: usize,
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8>> EcbOracle<F> {
+  fn new(oracle: F) -> Option<Self> {
+    // A long run of zeros encrypts to repeated blocks under ECB, whatever
+    // comes before it, and the smallest size that repeats is the block size
+    let ct = oracle(&[0u8; 3 * MAX_BLOCK_SIZE]);
+    let (block_size, zeros) = (8..=MAX_BLOCK_SIZE)
+      .filter(|block_size| ct.len() % block_size == 0)
+      .find_map(|block_size| Some((block_size, repeated_block(&ct, block_size)?)))?;
+    let ones = repeated_block(&oracle(&[1u8; 3 * MAX_BLOCK_SIZE]), block_size)?;
+
+    Some(EcbOracle {
+      oracle,
+      block_size,
+      markers: (zeros, ones),
+      filler: 0,
+      prefix_lengths: HashSet::new(),
+      queries: 2,
+    })
+  }
+
+  /// The ciphertext of `payload || secret` with the prefix cut off.
+  fn query(&mut self, payload: &[u8]) -> Result<Vec<u8>, ByteAtATimeError> {
+    let block_size = self.block_size;
+    for _ in 0..MAX_BLOCK_SIZE * block_size {
+      let input = [
+        &vec![FILL; self.filler][..],
+        &vec![0u8; block_size],
+        &vec![1u8; block_size],
+        payload,
+      ]
+      .concat();
+      let ct = (self.oracle)(&input);
+      self.queries += 1;
+
+      let blocks: Vec<_> = ct.chunks(block_size).collect();
+      let (zeros, ones) = &self.markers;
+      if let Some(block) = blocks
+        .windows(2)
+        .position(|pair| pair[0] == &zeros[..] && pair[1] == &ones[..])
+      {
+        let prefix_length = (block * block_size)
+          .checked_sub(self.filler)
+          .ok_or(ByteAtATimeError::Unaligned)?;
+        self.prefix_lengths.insert(prefix_length);
+        return Ok(ct[(block + 2) * block_size..].to_vec());
+      }
+      self.filler = (self.filler + 1) % block_size;
+    }
+    Err(ByteAtATimeError::Unaligned)
+  }
+}
+
+/// The first block of `ct` that's repeated straight after itself.
+fn repeated_block(ct: &[u8], block_size: usize) -> Option<Vec<u8>> {
+  let blocks: Vec<_> = ct.chunks(block_size).collect();
+  blocks
+    .windows(2)
+    .find(|pair| pair[0] == pair[1])
+    .map(|pair| pair[0].to_vec())
+}
+
+/// Byte-at-a-time ECB decryption. Given an oracle that encrypts
+/// `prefix || chosen || secret` under ECB with a fixed key, works out the
+/// block size, the prefix and the secret's length, and recovers the secret.
+pub fn byte_at_a_time_ecb<F: Fn(&[u8]) -> Vec<u8>>(
+  oracle: F,
+) -> Result<ByteAtATime, ByteAtATimeError> {
+  let mut oracle = EcbOracle::new(oracle).ok_or(ByteAtATimeError::NotEcb)?;
+  let block_size = oracle.block_size;
+
+  // Grow the input until the padding spills over into another block, at
+  // which point the secret and the input fill whole blocks exactly
+  let padded_length = oracle.query(&[])?.len();
+  let mut secret_length = None;
+  for extra in 1..=block_size {
+    if oracle.query(&vec![FILL; extra])?.len() > padded_length {
+      secret_length = Some(padded_length - extra);
+      break;
+    }
+  }
+  let secret_length = secret_length.ok_or(ByteAtATimeError::SecretLength)?;
+
+  // Line up each unknown byte at the end of a block. The ciphertext only
+  // depends on the offset, so there's one target query per offset. The
+  // dictionary of all 256 candidates for the byte fits in one query.
+  let mut targets = HashMap::new();
+  let mut known = vec![FILL; block_size - 1];
+  for i in 0..secret_length {
+    let offset = block_size - 1 - i % block_size;
+    if !targets.contains_key(&offset) {
+      targets.insert(offset, oracle.query(&vec![FILL; offset])?);
+    }
+    let block = i / block_size;
+    let target = &targets[&offset][block * block_size..(block + 1) * block_size];
+
+    let window = &known[known.len() - (block_size - 1)..];
+    let candidates: Vec<u8> = (0..=255).flat_map(|c| [window, &[c]].concat()).collect();
+    let byte = oracle
+      .query(&candidates)?
+      .chunks(block_size)
+      .take(256)
+      .position(|candidate| candidate == target)
+      .ok_or(ByteAtATimeError::UnknownByte(i))?;
+    known.push(byte as u8);
+  }
+
+  let prefix_length = match oracle.prefix_lengths.len() {
+    1 => oracle.prefix_lengths.iter().next().copied(),
+    _ => None,
+  };
+  Ok(ByteAtATime {
+    block_size,
+    prefix_length,
+    secret: known.split_off(block_size - 1),
+    queries: oracle.queries,
+  })
+}
+
+/// Answers whether `iv || ct` decrypts under CBC to correctly padded
+/// plaintext, and nothing more.
+pub trait PaddingOracle {
+  fn block_size(&self) -> usize;
+  fn has_valid_padding(&self, iv: &[u8], ct: &[u8]) -> bool;
+
+  /// How many IVs to hand `has_valid_paddings` at a time.
+  fn concurrency(&self) -> usize {
+    1
+  }
+
+  /// Checks several IVs against the same ciphertext. Oracles with a slow
+  /// round trip can override this to make the queries concurrently.
+  fn has_valid_paddings(&self, ivs: &[Vec<u8>], ct: &[u8]) -> Vec<bool> {
+    ivs
+      .iter()
+      .map(|iv| self.has_valid_padding(iv, ct))
+      .collect()
+  }
+}
+
+/// Recovers the block cipher decryption of `block`, before it gets XORed with
+/// the previous block, by forging the previous block one byte at a time.
+fn padding_oracle_block<O: PaddingOracle>(
+  oracle: &O,
+  previous: &[u8],
+  block: &[u8],
+) -> Option<Vec<u8>> {
+  let block_size = oracle.block_size();
+  let mut decrypted = vec![0u8; block_size];
+  let mut forged = previous.to_vec();
+
+  for i in (0..block_size).rev() {
+    // Make every byte after i decrypt to the padding we're aiming for
+    let pad = (block_size - i) as u8;
+    for j in i + 1..block_size {
+      forged[j] = decrypted[j] ^ pad;
+    }
+
+    let mut found = None;
+    let candidates: Vec<u8> = (0..=255).collect();
+    for batch in candidates.chunks(oracle.concurrency().max(1)) {
+      let ivs: Vec<_> = batch
+        .iter()
+        .map(|&c| {
+          forged[i] = c;
+          forged.clone()
+        })
+        .collect();
+      let valid = oracle.has_valid_paddings(&ivs, block);
+
+      // A valid last byte may be the tail of longer padding, like 02 02,
+      // rather than 01. Changing the byte before it only matters then.
+      found = batch.iter().zip(valid).find_map(|(&c, valid)| {
+        if !valid {
+          return None;
+        }
+        if i == block_size - 1 && i > 0 {
+          forged[i] = c;
+          forged[i - 1] ^= 1;
+          let single = oracle.has_valid_padding(&forged, block);
+          forged[i - 1] ^= 1;
+          if !single {
+            return None;
+          }
+        }
+        Some(c)
+      });
+      if found.is_some() {
+        break;
+      }
+    }
+    let found = found?;
+    decrypted[i] = found ^ pad;
+  }
+
+  Some(decrypted)
+}
+
+/// Decrypts CBC ciphertext, IV-adjacent block included, with nothing but a
+/// padding oracle. Returns `None` if the oracle gives inconsistent answers.
+pub fn padding_oracle_decrypt<O: PaddingOracle>(
+  oracle: &O,
+  iv: &[u8],
+  ct: &[u8],
+) -> Option<Vec<u8>> {
+  let block_size = oracle.block_size();
+  if iv.len() != block_size || ct.len() % block_size != 0 {
+    return None;
+  }
+
+  let mut pt = Vec::new();
+  let mut previous = iv;
+  for block in ct.chunks(block_size) {
+    pt.extend(xor(
+      &padding_oracle_block(oracle, previous, block)?,
+      previous,
+    ));
+    previous = block;
+  }
+  strip_pkcs7(&pt).ok()
+}
+
+/// CBC-R: encrypts any plaintext with a padding oracle standing in for the
+/// key. Working back from a random last block, each ciphertext block is
+/// chosen so the next one decrypts to the plaintext we want. Returns the IV
+/// and the ciphertext.
+pub fn padding_oracle_encrypt<O: PaddingOracle>(
+  oracle: &O,
+  pt: &[u8],
+) -> Option<(Vec<u8>, Vec<u8>)> {
+  let block_size = oracle.block_size();
+  let mut blocks = vec![random_bytes(block_size)];
+  for pt_block in pad_pkcs7(pt, block_size).chunks(block_size).rev() {
+    let next = &blocks[blocks.len() - 1];
+    let decrypted = padding_oracle_block(oracle, &vec![0u8; block_size], next)?;
+    blocks.push(xor(&decrypted, pt_block));
+  }
+
+  let iv = blocks.pop()?;
+  blocks.reverse();
+  Some((iv, blocks.concat()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::crypt::*;
+  use crate::english;
+  use rand::random;
+  use std::fs;
+  use std::ops::Range;
+
+  fn check_length_extension<H: Extendable>() {
+    let secret = b"YELLOW SUBMARINE";
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let mac = H::digest(&[&secret[..], message].concat());
+    let (forged_message, forged_mac) =
+      length_extension::<H>(message, &mac, secret.len(), b";admin=true");
+    assert!(forged_message.ends_with(b";admin=true"));
+    assert_eq!(
+      H::digest(&[&secret[..], &forged_message].concat()),
+      forged_mac
+    );
+  }
+
+  #[test]
+  fn length_extension_sha1() {
+    check_length_extension::<Sha1>();
+  }
+
+  #[test]
+  fn length_extension_sha256() {
+    check_length_extension::<Sha256>();
+  }
+
+  #[test]
+  fn length_extension_md5() {
+    check_length_extension::<Md5>();
+  }
+
+  #[test]
+  fn length_extension_md4() {
+    check_length_extension::<Md4>();
+  }
+
+  #[test]
+  fn breaks_reused_keystream() {
+    let keystream = random_bytes(20);
+    // Not what the n-grams were trained on
+    let cts: Vec<_> = fs::read_to_string("data/fury.txt")
+      .unwrap()
+      .lines()
+      .map(|line| xor(line.as_bytes(), &keystream))
+      .collect();
+    assert_eq!(
+      break_reused_keystream(&cts, 20, english::ngrams(), Scoring::NGram),
+      keystream
+    );
+  }
+
+  fn records(ids: Range<usize>) -> Vec<u8> {
+    let names = ["alice", "bob", "carol", "dave", "erin", "frank", "grace"];
+    let roles = ["admin", "editor", "viewer"];
+    ids
+      .map(|id| {
+        format!(
+          r#"{{"user":"{}","role":"{}","active":{}}}"#,
+          names[id * 3 % names.len()],
+          roles[id % roles.len()],
+          id % 4 != 0
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+      .into_bytes()
+  }
+
+  #[test]
+  fn breaks_reused_keystream_on_json() {
+    let model = LanguageModel::train(&records(0..200), 4);
+    let keystream = random_bytes(24);
+    let cts: Vec<_> = records(200..240)
+      .split(|&b| b == b'\n')
+      .map(|record| xor(record, &keystream))
+      .collect();
+    assert_eq!(
+      break_reused_keystream(&cts, 24, &model, Scoring::NGram),
+      keystream
+    );
+  }
+
+  #[test]
+  fn finds_mt_seeds() {
+    let seed = 1_600_000_000 + random::<u32>() % 1_000_000;
+    let mut generator = Rand::new(seed);
+    let outputs: Vec<_> = generator.by_ref().take(700).collect();
+    let window = seed - 200_000..=seed + 200_000;
+
+    let reports = Mutex::new(Vec::new());
+    let observed = [(3, outputs[3]), (100, outputs[100])];
+    let found = find_mt_seed(&observed, window.clone(), |tried| {
+      reports.lock().unwrap().push(tried)
+    });
+    assert_eq!(found, Some(seed));
+    assert!(!reports.into_inner().unwrap().is_empty());
+
+    // Past the first N - M outputs too
+    assert_eq!(
+      find_mt_seed(&[(650, outputs[650])], seed - 10..=seed, |_| ()),
+      Some(seed)
+    );
+    assert_eq!(find_mt_seed(&observed, 0..=1000, |_| ()), None);
+  }
+
+  #[test]
+  fn breaks_repeating_xor() {
+    let pt = fs::read_to_string("data/ice-ice-baby.txt").unwrap();
+    let key = b"Vanilla Ice";
+    let ct = cycled_xor(pt.as_bytes(), key);
+
+    for &metric in &[KeyLength::Hamming, KeyLength::IndexOfCoincidence] {
+      let ranked = rank_key_lengths(&ct, 40, metric);
+      assert_eq!(ranked.len(), 40);
+      assert!(ranked
+        .iter()
+        .take(3)
+        .any(|&(length, _)| length % key.len() == 0));
+    }
+
+    let found = break_repeating_xor(&ct, 40, 3, english::letters());
+    assert!(!found.is_empty() && found.len() <= 3);
+    assert_eq!(found[0].key, key);
+    assert_eq!(found[0].pt, pt.as_bytes());
+    assert!(found.windows(2).all(|pair| pair[0].score >= pair[1].score));
+  }
+
+  const SECRET: &[u8] = b"Rollin' in my 5.0\nWith my rag-top down so my hair can blow";
+
+  #[test]
+  fn byte_at_a_time_without_prefix() {
+    let key = random_16();
+    let found = byte_at_a_time_ecb(|pt| encrypt_ecb(&key, &[pt, SECRET].concat()).unwrap())
+      .expect("decrypts");
+    assert_eq!(found.block_size, 16);
+    assert_eq!(found.prefix_length, Some(0));
+    assert_eq!(found.secret, SECRET);
+  }
+
+  #[test]
+  fn byte_at_a_time_with_fixed_prefix() {
+    let cipher = tea(&random_16()).unwrap();
+    let prefix = random_bytes(21);
+    let found = byte_at_a_time_ecb(|pt| encrypt_ecb_with(&cipher, &[&prefix, pt, SECRET].concat()))
+      .expect("decrypts");
+    assert_eq!(found.block_size, 8);
+    assert_eq!(found.prefix_length, Some(21));
+    assert_eq!(found.secret, SECRET);
+  }
+
+  #[test]
+  fn byte_at_a_time_with_random_prefix() {
+    let key = random_16();
+    let found = byte_at_a_time_ecb(|pt| {
+      let prefix = random_bytes(rand::random::<usize>() % 40);
+      encrypt_ecb(&key, &[&prefix, pt, SECRET].concat()).unwrap()
+    })
+    .expect("decrypts");
+    assert_eq!(found.prefix_length, None);
+    assert_eq!(found.secret, SECRET);
+  }
+
+  #[test]
+  fn byte_at_a_time_with_prefix_ending_in_zeros() {
+    // Zeros at the end of the prefix run into the chosen zero block, which
+    // used to look lined up a block early
+    let key = random_16();
+    let prefix = [random_bytes(16), vec![0; 3]].concat();
+    let found =
+      byte_at_a_time_ecb(|pt| encrypt_ecb(&key, &[&prefix, pt, SECRET].concat()).unwrap())
+        .expect("decrypts");
+    assert_eq!(found.prefix_length, Some(19));
+    assert_eq!(found.secret, SECRET);
+
+    let found = byte_at_a_time_ecb(|pt| {
+      let prefix = [random_bytes(rand::random::<usize>() % 40), vec![0; 15]].concat();
+      encrypt_ecb(&key, &[&prefix, pt, SECRET].concat()).unwrap()
+    })
+    .expect("decrypts");
+    assert_eq!(found.secret, SECRET);
+  }
+
+  #[test]
+  fn byte_at_a_time_needs_ecb() {
+    let key = random_16();
+    let iv = random_16();
+    assert_eq!(
+      byte_at_a_time_ecb(|pt| encrypt_cbc(&key, &iv, pt).unwrap()).unwrap_err(),
+      ByteAtATimeError::NotEcb
+    );
+  }
+
+  struct CbcOracle<C: BlockCipher> {
+    cipher: C,
+  }
+
+  impl<C: BlockCipher> PaddingOracle for CbcOracle<C> {
+    fn block_size(&self) -> usize {
+      self.cipher.block_size()
+    }
+
+    fn has_valid_padding(&self, iv: &[u8], ct: &[u8]) -> bool {
+      decrypt_cbc_with(&self.cipher, iv, ct).is_ok()
+    }
+  }
+
+  fn check_padding_oracle<C: BlockCipher>(cipher: C) {
+    let oracle = CbcOracle { cipher };
+    let block_size = oracle.block_size();
+    // Every length of padding, so the last block is often ambiguous
+    for length in 0..=2 * block_size {
+      let pt = &SECRET[..length];
+      let iv = random_bytes(block_size);
+      let ct = encrypt_cbc_with(&oracle.cipher, &iv, pt).unwrap();
+      assert_eq!(padding_oracle_decrypt(&oracle, &iv, &ct).unwrap(), pt);
+    }
+
+    let (iv, ct) = padding_oracle_encrypt(&oracle, SECRET).unwrap();
+    assert_eq!(decrypt_cbc_with(&oracle.cipher, &iv, &ct).unwrap(), SECRET);
+  }
+
+  #[test]
+  fn padding_oracle_aes() {
+    check_padding_oracle(aes(&random_16()).unwrap());
+  }
+
+  #[test]
+  fn padding_oracle_tea() {
+    check_padding_oracle(tea(&random_16()).unwrap());
+  }
+
+  struct BatchedOracle(CbcOracle<Aes>);
+
+  impl PaddingOracle for BatchedOracle {
+    fn block_size(&self) -> usize {
+      16
+    }
+
+    fn has_valid_padding(&self, iv: &[u8], ct: &[u8]) -> bool {
+      self.0.has_valid_padding(iv, ct)
+    }
+
+    fn concurrency(&self) -> usize {
+      7
+    }
+  }
+
+  #[test]
+  fn padding_oracle_in_batches() {
+    let cipher = aes(&random_16()).unwrap();
+    let iv = random_16();
+    let ct = encrypt_cbc_with(&cipher, &iv, SECRET).unwrap();
+    let oracle = BatchedOracle(CbcOracle { cipher });
+    assert_eq!(padding_oracle_decrypt(&oracle, &iv, &ct).unwrap(), SECRET);
+  }
+}
@@ -1,99 +1,268 @@
+//! Block ciphers and their modes, hash functions and MACs, all written
+//! against our own traits so the attacks can reach inside them.
+
 use crate::bytes::*;
+use crate::error::{Error, Result};
 use crate::rand::*;
 use aes::cipher::generic_array::GenericArray;
-use aes::cipher::{BlockCipher, NewBlockCipher};
-use aes::Aes128;
-use sha2::{Digest, Sha256};
+use aes::cipher::NewBlockCipher;
+use aes::{Aes128, Aes192, Aes256};
+use std::convert::TryInto;
 
-pub fn aes128(key: &[u8]) -> Aes128 {
-  assert_eq!(key.len(), 16, "AES key length must be 16");
-  Aes128::new(&GenericArray::from_slice(key))
+/// A keyed block cipher that the ECB, CBC and CTR modes below can run over.
+pub trait BlockCipher {
+  fn block_size(&self) -> usize;
+  fn encrypt_block(&self, block: &[u8]) -> Vec<u8>;
+  fn decrypt_block(&self, block: &[u8]) -> Vec<u8>;
 }
 
-pub fn encrypt_block(cipher: &Aes128, block: &[u8]) -> Vec<u8> {
-  assert_eq!(block.len(), 16, "Block length must be 16 for encryption");
-  let mut copy = GenericArray::clone_from_slice(block);
-  cipher.encrypt_block(&mut copy);
-  copy.as_slice().to_vec()
+pub fn aes128(key: &[u8]) -> Result<Aes128> {
+  if key.len() != 16 {
+    return Err(Error::BadKeyLength);
+  }
+  Ok(Aes128::new(&GenericArray::from_slice(key)))
 }
 
-pub fn decrypt_block(cipher: &Aes128, block: &[u8]) -> Vec<u8> {
-  assert_eq!(block.len(), 16, "Block length must be 16 for decryption");
-  let mut copy = GenericArray::clone_from_slice(block);
-  cipher.decrypt_block(&mut copy);
-  copy.as_slice().to_vec()
+pub fn aes192(key: &[u8]) -> Result<Aes192> {
+  if key.len() != 24 {
+    return Err(Error::BadKeyLength);
+  }
+  Ok(Aes192::new(&GenericArray::from_slice(key)))
 }
 
-pub fn encrypt_ecb(key: &[u8], pt: &[u8]) -> Vec<u8> {
-  assert_eq!(key.len(), 16, "Key length must be 16 for encryption");
+pub fn aes256(key: &[u8]) -> Result<Aes256> {
+  if key.len() != 32 {
+    return Err(Error::BadKeyLength);
+  }
+  Ok(Aes256::new(&GenericArray::from_slice(key)))
+}
 
-  let cipher = aes128(key);
-  let ct: Vec<u8> = pad_pkcs7(pt, 16)
-    .chunks(16)
-    .flat_map(|block| encrypt_block(&cipher, block))
-    .collect();
+/// AES with the variant picked from the key length.
+pub enum Aes {
+  Aes128(Aes128),
+  Aes192(Aes192),
+  Aes256(Aes256),
+}
 
-  ct
+pub fn aes(key: &[u8]) -> Result<Aes> {
+  match key.len() {
+    16 => aes128(key).map(Aes::Aes128),
+    24 => aes192(key).map(Aes::Aes192),
+    32 => aes256(key).map(Aes::Aes256),
+    _ => Err(Error::BadKeyLength),
+  }
+}
+
+macro_rules! impl_aes_block_cipher {
+  ($($aes:ty),*) => {
+    $(
+      impl BlockCipher for $aes {
+        fn block_size(&self) -> usize {
+          16
+        }
+
+        fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+          assert_eq!(block.len(), 16, "Block length must be 16 for encryption");
+          let mut copy = GenericArray::clone_from_slice(block);
+          aes::cipher::BlockCipher::encrypt_block(self, &mut copy);
+          copy.as_slice().to_vec()
+        }
+
+        fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+          assert_eq!(block.len(), 16, "Block length must be 16 for decryption");
+          let mut copy = GenericArray::clone_from_slice(block);
+          aes::cipher::BlockCipher::decrypt_block(self, &mut copy);
+          copy.as_slice().to_vec()
+        }
+      }
+    )*
+  };
+}
+
+impl_aes_block_cipher!(Aes128, Aes192, Aes256);
+
+impl BlockCipher for Aes {
+  fn block_size(&self) -> usize {
+    16
+  }
+
+  fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+    match self {
+      Aes::Aes128(cipher) => cipher.encrypt_block(block),
+      Aes::Aes192(cipher) => cipher.encrypt_block(block),
+      Aes::Aes256(cipher) => cipher.encrypt_block(block),
+    }
+  }
+
+  fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+    match self {
+      Aes::Aes128(cipher) => cipher.decrypt_block(block),
+      Aes::Aes192(cipher) => cipher.decrypt_block(block),
+      Aes::Aes256(cipher) => cipher.decrypt_block(block),
+    }
+  }
+}
+
+/// TEA, a tiny cipher with a 64-bit block and a 128-bit key. Handy for
+/// checking that the modes and attacks don't assume 16 byte blocks.
+pub struct Tea {
+  key: [u32; 4],
+}
+
+const TEA_DELTA: u32 = 0x9E3779B9;
+
+pub fn tea(key: &[u8]) -> Result<Tea> {
+  if key.len() != 16 {
+    return Err(Error::BadKeyLength);
+  }
+  let mut words = [0u32; 4];
+  for (i, word) in key.chunks(4).enumerate() {
+    words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+  }
+  Ok(Tea { key: words })
+}
+
+impl BlockCipher for Tea {
+  fn block_size(&self) -> usize {
+    8
+  }
+
+  fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+    assert_eq!(block.len(), 8, "Block length must be 8 for encryption");
+    let [k0, k1, k2, k3] = self.key;
+    let mut v0 = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+    let mut v1 = u32::from_be_bytes([block[4], block[5], block[6], block[7]]);
+    let mut sum = 0u32;
+    for _ in 0..32 {
+      sum = sum.wrapping_add(TEA_DELTA);
+      v0 = v0.wrapping_add(
+        (v1 << 4).wrapping_add(k0) ^ v1.wrapping_add(sum) ^ (v1 >> 5).wrapping_add(k1),
+      );
+      v1 = v1.wrapping_add(
+        (v0 << 4).wrapping_add(k2) ^ v0.wrapping_add(sum) ^ (v0 >> 5).wrapping_add(k3),
+      );
+    }
+    [v0.to_be_bytes(), v1.to_be_bytes()].concat()
+  }
+
+  fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+    assert_eq!(block.len(), 8, "Block length must be 8 for decryption");
+    let [k0, k1, k2, k3] = self.key;
+    let mut v0 = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+    let mut v1 = u32::from_be_bytes([block[4], block[5], block[6], block[7]]);
+    let mut sum = TEA_DELTA.wrapping_mul(32);
+    for _ in 0..32 {
+      v1 = v1.wrapping_sub(
+        (v0 << 4).wrapping_add(k2) ^ v0.wrapping_add(sum) ^ (v0 >> 5).wrapping_add(k3),
+      );
+      v0 = v0.wrapping_sub(
+        (v1 << 4).wrapping_add(k0) ^ v1.wrapping_add(sum) ^ (v1 >> 5).wrapping_add(k1),
+      );
+      sum = sum.wrapping_sub(TEA_DELTA);
+    }
+    [v0.to_be_bytes(), v1.to_be_bytes()].concat()
+  }
 }
 
-pub fn decrypt_ecb(key: &[u8], ct: &[u8]) -> Result<Vec<u8>, &'static str> {
-  assert_eq!(key.len(), 16, "Key length must be 16 for decryption");
-  assert_eq!(ct.len() % 16, 0, "CT length must be divisible by 16");
+pub fn encrypt_ecb_with<C: BlockCipher>(cipher: &C, pt: &[u8]) -> Vec<u8> {
+  let block_size = cipher.block_size();
+  pad_pkcs7(pt, block_size)
+    .chunks(block_size)
+    .flat_map(|block| cipher.encrypt_block(block))
+    .collect()
+}
+
+pub fn decrypt_ecb_with<C: BlockCipher>(cipher: &C, ct: &[u8]) -> Result<Vec<u8>> {
+  let block_size = cipher.block_size();
+  if ct.len() % block_size != 0 {
+    return Err(Error::BadLength);
+  }
 
-  let cipher = aes128(key);
   let pt: Vec<u8> = ct
-    .chunks(16)
-    .flat_map(|block| decrypt_block(&cipher, block))
+    .chunks(block_size)
+    .flat_map(|block| cipher.decrypt_block(block))
     .collect();
   strip_pkcs7(&pt)
 }
 
-pub fn encrypt_cbc(key: &[u8], iv: &[u8], pt: &[u8]) -> Vec<u8> {
-  assert_eq!(key.len(), 16, "Key length must be 16 for encryption");
-  assert_eq!(iv.len(), 16, "IV length must be 16 for encryption");
+pub fn encrypt_cbc_with<C: BlockCipher>(cipher: &C, iv: &[u8], pt: &[u8]) -> Result<Vec<u8>> {
+  let block_size = cipher.block_size();
+  if iv.len() != block_size {
+    return Err(Error::BadLength);
+  }
 
-  let cipher = aes128(key);
   let mut carry = iv.to_owned();
   let mut ct = Vec::new();
-  for block in pad_pkcs7(pt, 16).chunks(16) {
-    let ct_block = &encrypt_block(&cipher, &xor(block, &carry));
+  for block in pad_pkcs7(pt, block_size).chunks(block_size) {
+    let ct_block = &cipher.encrypt_block(&xor(block, &carry));
     ct.extend_from_slice(ct_block);
     carry = ct_block.to_owned();
   }
 
-  ct
+  Ok(ct)
 }
 
-pub fn decrypt_cbc(key: &[u8], iv: &[u8], ct: &[u8]) -> Result<Vec<u8>, &'static str> {
-  assert_eq!(key.len(), 16, "Key length must be 16 for decryption");
-  assert_eq!(iv.len(), 16, "IV length must be 16 for decryption");
-  assert_eq!(ct.len() % 16, 0, "CT length must be divisible by 16");
+pub fn decrypt_cbc_with<C: BlockCipher>(cipher: &C, iv: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+  let block_size = cipher.block_size();
+  if iv.len() != block_size || ct.len() % block_size != 0 {
+    return Err(Error::BadLength);
+  }
 
-  let cipher = aes128(key);
   let mut carry = iv.to_owned();
   let mut pt = Vec::new();
-  for block in ct.chunks(16) {
-    pt.extend_from_slice(&xor(&carry, &decrypt_block(&cipher, block)));
+  for block in ct.chunks(block_size) {
+    pt.extend_from_slice(&xor(&carry, &cipher.decrypt_block(block)));
     carry = block.to_owned();
   }
 
   strip_pkcs7(&pt)
 }
 
-pub fn encrypt_ctr(key: &[u8], nonce: &[u8; 8], pt: &[u8]) -> Vec<u8> {
-  assert_eq!(key.len(), 16, "Key length must be 16 for decryption");
-  assert_eq!(nonce.len(), 8, "Nonce length must be 8 for decryption");
-  let cipher = aes128(key);
+/// CTR mode with a counter block of `nonce || counter`, where the counter is
+/// little endian and fills whatever the nonce leaves of the block.
+pub fn encrypt_ctr_with<C: BlockCipher>(cipher: &C, nonce: &[u8], pt: &[u8]) -> Result<Vec<u8>> {
+  let block_size = cipher.block_size();
+  if nonce.len() >= block_size {
+    return Err(Error::BadLength);
+  }
+
+  let counter_size = block_size - nonce.len();
   let mut ct = Vec::new();
-  for (count, block) in pt.chunks(16).enumerate() {
-    let pad = &encrypt_block(&cipher, &[*nonce, count.to_le_bytes()].concat());
-    ct.extend_from_slice(&xor(&pad, block));
+  for (count, block) in pt.chunks(block_size).enumerate() {
+    let mut counter = (count as u64).to_le_bytes().to_vec();
+    counter.resize(counter_size, 0);
+    let pad = &cipher.encrypt_block(&[nonce, &counter].concat());
+    ct.extend_from_slice(&xor(pad, block));
   }
 
-  ct
+  Ok(ct)
+}
+
+pub fn decrypt_ctr_with<C: BlockCipher>(cipher: &C, nonce: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+  encrypt_ctr_with(cipher, nonce, ct)
 }
 
-pub fn decrypt_ctr(key: &[u8], nonce: &[u8; 8], ct: &[u8]) -> Vec<u8> {
+pub fn encrypt_ecb(key: &[u8], pt: &[u8]) -> Result<Vec<u8>> {
+  Ok(encrypt_ecb_with(&aes(key)?, pt))
+}
+
+pub fn decrypt_ecb(key: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+  decrypt_ecb_with(&aes(key)?, ct)
+}
+
+pub fn encrypt_cbc(key: &[u8], iv: &[u8], pt: &[u8]) -> Result<Vec<u8>> {
+  encrypt_cbc_with(&aes(key)?, iv, pt)
+}
+
+pub fn decrypt_cbc(key: &[u8], iv: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+  decrypt_cbc_with(&aes(key)?, iv, ct)
+}
+
+pub fn encrypt_ctr(key: &[u8], nonce: &[u8; 8], pt: &[u8]) -> Result<Vec<u8>> {
+  encrypt_ctr_with(&aes(key)?, nonce, pt)
+}
+
+pub fn decrypt_ctr(key: &[u8], nonce: &[u8; 8], ct: &[u8]) -> Result<Vec<u8>> {
   encrypt_ctr(key, nonce, ct)
 }
 
@@ -111,24 +280,193 @@ pub fn decrypt_mt(seed: u32, ct: &[u8]) -> Vec<u8> {
   encrypt_mt(seed, ct)
 }
 
-const SHA1_LENGTH: usize = 20;
+/// A hash that can be fed its input a piece at a time.
+pub trait Hasher: Sized {
+  const BLOCK_SIZE: usize;
+  const OUTPUT_SIZE: usize;
 
-pub fn sha1(message: &[u8]) -> [u8; SHA1_LENGTH] {
-  let initial = [
-    0x67452301u32,
-    0xEFCDAB89,
-    0x98BADCFE,
-    0x10325476,
-    0xC3D2E1F0,
-  ];
-  sha1_extend(message, &initial, 0)
+  fn new() -> Self;
+  fn update(&mut self, data: &[u8]);
+  fn finalize(self) -> Vec<u8>;
+
+  fn digest(message: &[u8]) -> Vec<u8> {
+    let mut hasher = Self::new();
+    hasher.update(message);
+    hasher.finalize()
+  }
 }
 
-pub fn sha1_extend(
-  message: &[u8],
-  initial: &[u32; SHA1_LENGTH / 4],
-  length_offset: usize,
-) -> [u8; SHA1_LENGTH] {
+/// A Merkle-Damgard hash whose digest is its whole internal state, so hashing
+/// can pick up again from any digest. That's a length extension attack.
+pub trait Extendable: Hasher {
+  /// The padding appended to a message of `length` bytes.
+  fn padding(length: usize) -> Vec<u8>;
+
+  /// Resume from a digest, where `length` is the number of bytes that went
+  /// into it, including the padding.
+  fn from_digest(digest: &[u8], length: usize) -> Self;
+}
+
+/// Input buffered into the 64 byte blocks that MD4, MD5, SHA-1 and SHA-256
+/// compress, along with the total length for the final padding.
+#[derive(Clone, Debug)]
+struct Blocks {
+  buffer: Vec<u8>,
+  length: usize,
+}
+
+impl Blocks {
+  fn new(length: usize) -> Blocks {
+    Blocks {
+      buffer: Vec::new(),
+      length,
+    }
+  }
+
+  fn update(&mut self, data: &[u8], mut compress: impl FnMut(&[u8])) {
+    self.length += data.len();
+    self.buffer.extend_from_slice(data);
+    let end = self.buffer.len() - self.buffer.len() % 64;
+    for block in self.buffer[..end].chunks(64) {
+      compress(block);
+    }
+    self.buffer.drain(..end);
+  }
+
+  fn finalize(mut self, big_endian: bool, mut compress: impl FnMut(&[u8])) {
+    // Pad relative to what is buffered so a hash resumed at an odd offset
+    // still lines up, but encode the full length
+    let padding = md_padding(self.buffer.len(), self.length, big_endian);
+    self.buffer.extend_from_slice(&padding);
+    for block in self.buffer.chunks(64) {
+      compress(block);
+    }
+  }
+}
+
+fn md_padding(position: usize, length: usize, big_endian: bool) -> Vec<u8> {
+  let bits = length as u64 * 8;
+  let encoded_length = if big_endian {
+    bits.to_be_bytes()
+  } else {
+    bits.to_le_bytes()
+  };
+  [
+    &[1u8 << 7],
+    &vec![0; 63 - ((position + 8) % 64)][..],
+    &encoded_length,
+  ]
+  .concat()
+}
+
+fn words_be(bytes: &[u8], words: &mut [u32]) {
+  for (i, b) in bytes.chunks(4).enumerate() {
+    words[i] = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+  }
+}
+
+fn words_le(bytes: &[u8], words: &mut [u32]) {
+  for (i, b) in bytes.chunks(4).enumerate() {
+    words[i] = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+  }
+}
+
+macro_rules! impl_hasher_io {
+  ($($hasher:ty),*) => {
+    $(
+      impl Default for $hasher {
+        fn default() -> Self {
+          Self::new()
+        }
+      }
+
+      impl std::io::Write for $hasher {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+          self.update(buf);
+          Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+          Ok(())
+        }
+      }
+    )*
+  };
+}
+
+pub const SHA1_LENGTH: usize = 20;
+const SHA1_INITIAL: [u32; SHA1_LENGTH / 4] = [
+  0x67452301u32,
+  0xEFCDAB89,
+  0x98BADCFE,
+  0x10325476,
+  0xC3D2E1F0,
+];
+
+/// Incremental SHA-1, which can also be resumed from a digest or raw state.
+#[derive(Clone, Debug)]
+pub struct Sha1 {
+  state: [u32; SHA1_LENGTH / 4],
+  blocks: Blocks,
+}
+
+impl Sha1 {
+  /// Resume from raw state words after `length` bytes have been hashed.
+  pub fn from_state(state: &[u32; SHA1_LENGTH / 4], length: usize) -> Sha1 {
+    Sha1 {
+      state: *state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// The chaining state and the number of bytes hashed so far. The state
+  /// only covers whole blocks, so it lags behind `length` until a block
+  /// boundary.
+  pub fn state(&self) -> ([u32; SHA1_LENGTH / 4], usize) {
+    (self.state, self.blocks.length)
+  }
+}
+
+impl Hasher for Sha1 {
+  const BLOCK_SIZE: usize = 64;
+  const OUTPUT_SIZE: usize = SHA1_LENGTH;
+
+  fn new() -> Sha1 {
+    Sha1::from_state(&SHA1_INITIAL, 0)
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self
+      .blocks
+      .update(data, |block| sha1_compress(state, block));
+  }
+
+  fn finalize(self) -> Vec<u8> {
+    let mut state = self.state;
+    self
+      .blocks
+      .finalize(true, |block| sha1_compress(&mut state, block));
+    state
+      .iter()
+      .flat_map(|&w| w.to_be_bytes().to_vec())
+      .collect()
+  }
+}
+
+impl Extendable for Sha1 {
+  fn padding(length: usize) -> Vec<u8> {
+    md_padding(length, length, true)
+  }
+
+  fn from_digest(digest: &[u8], length: usize) -> Sha1 {
+    let mut state = [0u32; SHA1_LENGTH / 4];
+    words_be(digest, &mut state);
+    Sha1::from_state(&state, length)
+  }
+}
+
+fn sha1_compress(state: &mut [u32; SHA1_LENGTH / 4], m: &[u8]) {
   let f = |t: usize, b: u32, c: u32, d: u32| match t {
     0..=19 => (b & c) | (!b & d),
     20..=39 => b ^ c ^ d,
@@ -145,60 +483,54 @@ pub fn sha1_extend(
     _ => panic!("Invalid value {} for t", t),
   };
 
-  let mut digest = initial.clone();
+  let mut w = [0u32; 80];
 
-  // Pad the message and split into chunks to process
-  for m in [
-    message,
-    &[1u8 << 7],
-    &vec![0; 63 - ((message.len() + 8) % 64)][..],
-    &((message.len() + length_offset) as u64 * 8).to_be_bytes(),
-  ]
-  .concat()
-  .chunks(16 * 4)
-  {
-    let mut w = [0u32; 80];
+  // 1.a
+  words_be(m, &mut w[..16]);
 
-    // 1.a
-    for i in 0..16 {
-      w[i] = u32::from_be_bytes([m[i * 4], m[i * 4 + 1], m[i * 4 + 2], m[i * 4 + 3]]);
-    }
-
-    // 1.b
-    for t in 16..80 {
-      w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
-    }
+  // 1.b
+  for t in 16..80 {
+    w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+  }
 
-    // 1.c
-    let [mut a, mut b, mut c, mut d, mut e] = digest;
-
-    // 1.d
-    for t in 0..80 {
-      let temp = a.rotate_left(5) + f(t, b, c, d) + e + w[t] + k(t);
-      e = d;
-      d = c;
-      c = b.rotate_left(30);
-      b = a;
-      a = temp;
-    }
+  // 1.c
+  let [mut a, mut b, mut c, mut d, mut e] = *state;
 
-    //  1.e.
-    digest[0] += a;
-    digest[1] += b;
-    digest[2] += c;
-    digest[3] += d;
-    digest[4] += e;
+  // 1.d
+  for (t, &w_t) in w.iter().enumerate() {
+    let temp = a
+      .rotate_left(5)
+      .wrapping_add(f(t, b, c, d))
+      .wrapping_add(e)
+      .wrapping_add(w_t)
+      .wrapping_add(k(t));
+    e = d;
+    d = c;
+    c = b.rotate_left(30);
+    b = a;
+    a = temp;
   }
 
-  let mut result = [0u8; SHA1_LENGTH];
-  for (i, b) in digest
-    .iter()
-    .flat_map(|&w| w.to_be_bytes().to_vec())
-    .enumerate()
-  {
-    result[i] = b;
-  }
-  result
+  //  1.e.
+  state[0] = state[0].wrapping_add(a);
+  state[1] = state[1].wrapping_add(b);
+  state[2] = state[2].wrapping_add(c);
+  state[3] = state[3].wrapping_add(d);
+  state[4] = state[4].wrapping_add(e);
+}
+
+pub fn sha1(message: &[u8]) -> [u8; SHA1_LENGTH] {
+  Sha1::digest(message).try_into().unwrap()
+}
+
+pub fn sha1_extend(
+  message: &[u8],
+  initial: &[u32; SHA1_LENGTH / 4],
+  length_offset: usize,
+) -> [u8; SHA1_LENGTH] {
+  let mut hasher = Sha1::from_state(initial, length_offset);
+  hasher.update(message);
+  hasher.finalize().try_into().unwrap()
 }
 
 pub fn sha1_mac(key: &[u8], message: &[u8]) -> [u8; SHA1_LENGTH] {
@@ -206,30 +538,230 @@ pub fn sha1_mac(key: &[u8], message: &[u8]) -> [u8; SHA1_LENGTH] {
 }
 
 pub const SHA256_LENGTH: usize = 32;
+pub const SHA224_LENGTH: usize = 28;
+const SHA256_INITIAL: [u32; 8] = [
+  0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+const SHA224_INITIAL: [u32; 8] = [
+  0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+const SHA256_K: [u32; 64] = [
+  0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+  0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+  0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+  0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+  0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+  0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+  0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+  0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
 
-pub fn sha256(message: &[u8]) -> [u8; SHA256_LENGTH] {
-  let mut digest = [0u8; SHA256_LENGTH];
-  let mut hasher = Sha256::new();
-  hasher.update(message);
-  let result = hasher.finalize();
-  for i in 0..SHA256_LENGTH {
-    digest[i] = result[i];
+/// Incremental SHA-256, which can also be resumed from a digest or raw state.
+#[derive(Clone, Debug)]
+pub struct Sha256 {
+  state: [u32; 8],
+  blocks: Blocks,
+}
+
+impl Sha256 {
+  /// Resume from raw state words after `length` bytes have been hashed.
+  pub fn from_state(state: &[u32; 8], length: usize) -> Sha256 {
+    Sha256 {
+      state: *state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// The chaining state and the number of bytes hashed so far.
+  pub fn state(&self) -> ([u32; 8], usize) {
+    (self.state, self.blocks.length)
   }
-  digest
 }
 
-const MD5_LENGTH: usize = 16;
+impl Hasher for Sha256 {
+  const BLOCK_SIZE: usize = 64;
+  const OUTPUT_SIZE: usize = SHA256_LENGTH;
 
-pub fn md5(message: &[u8]) -> [u8; MD5_LENGTH] {
-  let initial = [0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476];
-  md5_extend(message, &initial, 0)
+  fn new() -> Sha256 {
+    Sha256::from_state(&SHA256_INITIAL, 0)
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self
+      .blocks
+      .update(data, |block| sha256_compress(state, block));
+  }
+
+  fn finalize(self) -> Vec<u8> {
+    let mut state = self.state;
+    self
+      .blocks
+      .finalize(true, |block| sha256_compress(&mut state, block));
+    state
+      .iter()
+      .flat_map(|&w| w.to_be_bytes().to_vec())
+      .collect()
+  }
 }
 
-pub fn md5_extend(
+impl Extendable for Sha256 {
+  fn padding(length: usize) -> Vec<u8> {
+    md_padding(length, length, true)
+  }
+
+  fn from_digest(digest: &[u8], length: usize) -> Sha256 {
+    let mut state = [0u32; 8];
+    words_be(digest, &mut state);
+    Sha256::from_state(&state, length)
+  }
+}
+
+/// SHA-224 is SHA-256 with another IV and a truncated output. The truncation
+/// hides part of the state, so it can't be resumed from a digest.
+#[derive(Clone, Debug)]
+pub struct Sha224(Sha256);
+
+impl Hasher for Sha224 {
+  const BLOCK_SIZE: usize = 64;
+  const OUTPUT_SIZE: usize = SHA224_LENGTH;
+
+  fn new() -> Sha224 {
+    Sha224(Sha256::from_state(&SHA224_INITIAL, 0))
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    self.0.update(data)
+  }
+
+  fn finalize(self) -> Vec<u8> {
+    let mut digest = self.0.finalize();
+    digest.truncate(SHA224_LENGTH);
+    digest
+  }
+}
+
+fn sha256_compress(state: &mut [u32; 8], m: &[u8]) {
+  let mut w = [0u32; 64];
+  words_be(m, &mut w[..16]);
+  for t in 16..64 {
+    let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+    let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+    w[t] = w[t - 16]
+      .wrapping_add(s0)
+      .wrapping_add(w[t - 7])
+      .wrapping_add(s1);
+  }
+
+  let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+  for (&k_t, &w_t) in SHA256_K.iter().zip(w.iter()) {
+    let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+    let ch = (e & f) ^ (!e & g);
+    let temp1 = h
+      .wrapping_add(s1)
+      .wrapping_add(ch)
+      .wrapping_add(k_t)
+      .wrapping_add(w_t);
+    let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+    let maj = (a & b) ^ (a & c) ^ (b & c);
+    let temp2 = s0.wrapping_add(maj);
+
+    h = g;
+    g = f;
+    f = e;
+    e = d.wrapping_add(temp1);
+    d = c;
+    c = b;
+    b = a;
+    a = temp1.wrapping_add(temp2);
+  }
+
+  for (word, x) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+    *word = word.wrapping_add(*x);
+  }
+}
+
+pub fn sha256(message: &[u8]) -> [u8; SHA256_LENGTH] {
+  Sha256::digest(message).try_into().unwrap()
+}
+
+pub fn sha256_extend(
   message: &[u8],
-  initial: &[u32; MD5_LENGTH / 4],
+  initial: &[u32; SHA256_LENGTH / 4],
   length_offset: usize,
-) -> [u8; MD5_LENGTH] {
+) -> [u8; SHA256_LENGTH] {
+  let mut hasher = Sha256::from_state(initial, length_offset);
+  hasher.update(message);
+  hasher.finalize().try_into().unwrap()
+}
+
+pub fn sha224(message: &[u8]) -> [u8; SHA224_LENGTH] {
+  Sha224::digest(message).try_into().unwrap()
+}
+
+pub const MD5_LENGTH: usize = 16;
+const MD5_INITIAL: [u32; MD5_LENGTH / 4] = [0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// Incremental MD5, which can also be resumed from a digest or raw state.
+#[derive(Clone, Debug)]
+pub struct Md5 {
+  state: [u32; MD5_LENGTH / 4],
+  blocks: Blocks,
+}
+
+impl Md5 {
+  /// Resume from raw state words after `length` bytes have been hashed.
+  pub fn from_state(state: &[u32; MD5_LENGTH / 4], length: usize) -> Md5 {
+    Md5 {
+      state: *state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// The chaining state and the number of bytes hashed so far.
+  pub fn state(&self) -> ([u32; MD5_LENGTH / 4], usize) {
+    (self.state, self.blocks.length)
+  }
+}
+
+impl Hasher for Md5 {
+  const BLOCK_SIZE: usize = 64;
+  const OUTPUT_SIZE: usize = MD5_LENGTH;
+
+  fn new() -> Md5 {
+    Md5::from_state(&MD5_INITIAL, 0)
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self.blocks.update(data, |block| md5_compress(state, block));
+  }
+
+  fn finalize(self) -> Vec<u8> {
+    let mut state = self.state;
+    self
+      .blocks
+      .finalize(false, |block| md5_compress(&mut state, block));
+    state
+      .iter()
+      .flat_map(|&w| w.to_le_bytes().to_vec())
+      .collect()
+  }
+}
+
+impl Extendable for Md5 {
+  fn padding(length: usize) -> Vec<u8> {
+    md_padding(length, length, false)
+  }
+
+  fn from_digest(digest: &[u8], length: usize) -> Md5 {
+    let mut state = [0u32; MD5_LENGTH / 4];
+    words_le(digest, &mut state);
+    Md5::from_state(&state, length)
+  }
+}
+
+fn md5_compress(state: &mut [u32; MD5_LENGTH / 4], block: &[u8]) {
   // s specifies the per-round shift amounts
   let s: [u32; 64] = [
     7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
@@ -248,87 +780,657 @@ pub fn md5_extend(
     0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
   ];
 
-  let mut digest = initial.clone();
+  let mut m = [0u32; 16];
+  words_le(block, &mut m);
+  let [mut a, mut b, mut c, mut d] = *state;
 
-  // Pad the message and split into chunks to process
-  for message in [
-    message,
-    &[1u8 << 7],
-    &vec![0; 63 - ((message.len() + 8) % 64)][..],
-    &((message.len() + length_offset) as u64 * 8).to_le_bytes(),
-  ]
-  .concat()
-  .chunks(16 * 4)
-  {
-    let mut m = [0u32; 16];
-    for (i, b) in message.chunks(4).enumerate() {
-      m[i] = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
-    }
-    let [mut a, mut b, mut c, mut d] = digest;
-
-    for i in 0usize..64 {
-      let (mut f, g) = match i {
-        0..=15 => ((b & c) | (!b & d), i),
-        16..=31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
-        32..=47 => (b ^ c ^ d, (3 * i + 5) % 16),
-        48..=63 => (c ^ (b | !d), (7 * i) % 16),
-        _ => panic!(),
-      };
-
-      f = f
-        .overflowing_add(a)
-        .0
-        .overflowing_add(k[i])
-        .0
-        .overflowing_add(m[g])
-        .0;
-      a = d;
-      d = c;
-      c = b;
-      b = b.overflowing_add(f.rotate_left(s[i])).0;
-    }
+  for i in 0usize..64 {
+    let (mut f, g) = match i {
+      0..=15 => ((b & c) | (!b & d), i),
+      16..=31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+      32..=47 => (b ^ c ^ d, (3 * i + 5) % 16),
+      48..=63 => (c ^ (b | !d), (7 * i) % 16),
+      _ => panic!(),
+    };
 
-    digest[0] = digest[0].overflowing_add(a).0;
-    digest[1] = digest[1].overflowing_add(b).0;
-    digest[2] = digest[2].overflowing_add(c).0;
-    digest[3] = digest[3].overflowing_add(d).0;
+    f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+    a = d;
+    d = c;
+    c = b;
+    b = b.wrapping_add(f.rotate_left(s[i]));
   }
 
-  let mut result = [0u8; MD5_LENGTH];
-  for (i, b) in digest
-    .iter()
-    .flat_map(|&w| w.to_le_bytes().to_vec())
-    .enumerate()
-  {
-    result[i] = b;
-  }
-  result
+  state[0] = state[0].wrapping_add(a);
+  state[1] = state[1].wrapping_add(b);
+  state[2] = state[2].wrapping_add(c);
+  state[3] = state[3].wrapping_add(d);
+}
+
+pub fn md5(message: &[u8]) -> [u8; MD5_LENGTH] {
+  Md5::digest(message).try_into().unwrap()
+}
+
+pub fn md5_extend(
+  message: &[u8],
+  initial: &[u32; MD5_LENGTH / 4],
+  length_offset: usize,
+) -> [u8; MD5_LENGTH] {
+  let mut hasher = Md5::from_state(initial, length_offset);
+  hasher.update(message);
+  hasher.finalize().try_into().unwrap()
 }
 
 pub fn md5_mac(key: &[u8], message: &[u8]) -> [u8; MD5_LENGTH] {
   md5(&[key, message].concat())
 }
 
-pub fn hmac_sha1(key: &[u8; 64], message: &[u8]) -> [u8; SHA1_LENGTH] {
-  let o_key_pad = xor(key, &[0x5c; 64]);
-  let i_key_pad = xor(key, &[0x36; 64]);
+pub const MD4_LENGTH: usize = 16;
+const MD4_INITIAL: [u32; MD4_LENGTH / 4] = MD5_INITIAL;
+
+/// Which message word each of the 48 MD4 steps adds in.
+pub const MD4_WORDS: [usize; 48] = [
+  0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3,
+  7, 11, 15, 0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15,
+];
+
+/// The left rotation of each of the 48 MD4 steps.
+pub const MD4_SHIFTS: [u32; 48] = [
+  3, 7, 11, 19, 3, 7, 11, 19, 3, 7, 11, 19, 3, 7, 11, 19, 3, 5, 9, 13, 3, 5, 9, 13, 3, 5, 9, 13, 3,
+  5, 9, 13, 3, 9, 11, 15, 3, 9, 11, 15, 3, 9, 11, 15, 3, 9, 11, 15,
+];
+
+/// Incremental MD4, which can also be resumed from a digest or raw state.
+#[derive(Clone, Debug)]
+pub struct Md4 {
+  state: [u32; MD4_LENGTH / 4],
+  blocks: Blocks,
+}
+
+impl Md4 {
+  /// Resume from raw state words after `length` bytes have been hashed.
+  pub fn from_state(state: &[u32; MD4_LENGTH / 4], length: usize) -> Md4 {
+    Md4 {
+      state: *state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// The chaining state and the number of bytes hashed so far.
+  pub fn state(&self) -> ([u32; MD4_LENGTH / 4], usize) {
+    (self.state, self.blocks.length)
+  }
+}
+
+impl Hasher for Md4 {
+  const BLOCK_SIZE: usize = 64;
+  const OUTPUT_SIZE: usize = MD4_LENGTH;
 
-  // sha1([&o_key_pad, &sha1(&[&i_key_pad, message].concat())].concat())
-  let inner = sha1(&[&i_key_pad, message].concat()).to_vec();
-  sha1(&[o_key_pad, inner].concat())
+  fn new() -> Md4 {
+    Md4::from_state(&MD4_INITIAL, 0)
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self.blocks.update(data, |block| md4_compress(state, block));
+  }
+
+  fn finalize(self) -> Vec<u8> {
+    let mut state = self.state;
+    self
+      .blocks
+      .finalize(false, |block| md4_compress(&mut state, block));
+    state
+      .iter()
+      .flat_map(|&w| w.to_le_bytes().to_vec())
+      .collect()
+  }
+}
+
+impl Extendable for Md4 {
+  fn padding(length: usize) -> Vec<u8> {
+    md_padding(length, length, false)
+  }
+
+  fn from_digest(digest: &[u8], length: usize) -> Md4 {
+    let mut state = [0u32; MD4_LENGTH / 4];
+    words_le(digest, &mut state);
+    Md4::from_state(&state, length)
+  }
 }
 
-pub fn hmac_sha256(key: &[u8; SHA256_LENGTH], message: &[u8]) -> [u8; SHA256_LENGTH] {
-  let o_key_pad = xor(key, &[0x5c; SHA256_LENGTH]);
-  let i_key_pad = xor(key, &[0x36; SHA256_LENGTH]);
-  let inner = sha256(&[&i_key_pad, message].concat()).to_vec();
-  sha256(&[o_key_pad, inner].concat())
+fn md4_round(step: usize) -> (fn(u32, u32, u32) -> u32, u32) {
+  match step {
+    0..=15 => (|x, y, z| (x & y) | (!x & z), 0),
+    16..=31 => (|x, y, z| (x & y) | (x & z) | (y & z), 0x5A827999),
+    32..=47 => (|x, y, z| x ^ y ^ z, 0x6ED9EBA1),
+    _ => panic!("Invalid MD4 step {}", step),
+  }
+}
+
+/// Every register value one MD4 compression goes through, in the order they
+/// are computed. `q[0..4]` hold the initial a, d, c and b, and step `i`
+/// writes `q[i + 4]` from the four values before it. This is the view a
+/// message modification collision search works in: conditions are stated on
+/// bits of `q`, and round 1 can be inverted with [`md4_round1_word`].
+pub fn md4_steps(state: &[u32; MD4_LENGTH / 4], m: &[u32; 16]) -> [u32; 52] {
+  let [a, b, c, d] = *state;
+  let mut q = [0u32; 52];
+  q[..4].copy_from_slice(&[a, d, c, b]);
+  for i in 0..48 {
+    let (f, k) = md4_round(i);
+    q[i + 4] = q[i]
+      .wrapping_add(f(q[i + 3], q[i + 2], q[i + 1]))
+      .wrapping_add(m[MD4_WORDS[i]])
+      .wrapping_add(k)
+      .rotate_left(MD4_SHIFTS[i]);
+  }
+  q
+}
+
+/// The message word that makes round 1 step `i` write `q[i + 4]`, given the
+/// four register values before it. After adjusting bits of `q` to meet a
+/// collision condition, this recomputes the message to match.
+pub fn md4_round1_word(q: &[u32], i: usize) -> u32 {
+  assert!(i < 16, "Only round 1 steps map directly to message words");
+  let (f, _) = md4_round(i);
+  q[i + 4]
+    .rotate_right(MD4_SHIFTS[i])
+    .wrapping_sub(q[i])
+    .wrapping_sub(f(q[i + 3], q[i + 2], q[i + 1]))
+}
+
+/// The chaining state after compressing the block that produced `q`.
+pub fn md4_state_from_steps(state: &[u32; MD4_LENGTH / 4], q: &[u32; 52]) -> [u32; MD4_LENGTH / 4] {
+  [
+    state[0].wrapping_add(q[48]),
+    state[1].wrapping_add(q[51]),
+    state[2].wrapping_add(q[50]),
+    state[3].wrapping_add(q[49]),
+  ]
+}
+
+fn md4_compress(state: &mut [u32; MD4_LENGTH / 4], block: &[u8]) {
+  let mut m = [0u32; 16];
+  words_le(block, &mut m);
+  *state = md4_state_from_steps(state, &md4_steps(state, &m));
+}
+
+pub fn md4(message: &[u8]) -> [u8; MD4_LENGTH] {
+  Md4::digest(message).try_into().unwrap()
+}
+
+pub fn md4_extend(
+  message: &[u8],
+  initial: &[u32; MD4_LENGTH / 4],
+  length_offset: usize,
+) -> [u8; MD4_LENGTH] {
+  let mut hasher = Md4::from_state(initial, length_offset);
+  hasher.update(message);
+  hasher.finalize().try_into().unwrap()
+}
+
+pub fn md4_mac(key: &[u8], message: &[u8]) -> [u8; MD4_LENGTH] {
+  md4(&[key, message].concat())
+}
+
+impl_hasher_io!(Sha1, Sha256, Sha224, Md5, Md4);
+
+/// HMAC over any hash, per RFC 2104. Keys longer than the hash's block are
+/// hashed first and shorter ones are zero padded.
+pub fn hmac<H: Hasher>(key: &[u8], message: &[u8]) -> Vec<u8> {
+  let mut block_key = if key.len() > H::BLOCK_SIZE {
+    H::digest(key)
+  } else {
+    key.to_vec()
+  };
+  block_key.resize(H::BLOCK_SIZE, 0);
+
+  let o_key_pad = xor(&block_key, &vec![0x5c; H::BLOCK_SIZE]);
+  let i_key_pad = xor(&block_key, &vec![0x36; H::BLOCK_SIZE]);
+
+  // H(o_key_pad || H(i_key_pad || message))
+  let mut inner = H::new();
+  inner.update(&i_key_pad);
+  inner.update(message);
+  let mut outer = H::new();
+  outer.update(&o_key_pad);
+  outer.update(&inner.finalize());
+  outer.finalize()
+}
+
+pub fn hmac_sha1(key: &[u8], message: &[u8]) -> [u8; SHA1_LENGTH] {
+  hmac::<Sha1>(key, message).try_into().unwrap()
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; SHA256_LENGTH] {
+  hmac::<Sha256>(key, message).try_into().unwrap()
+}
+
+pub fn hmac_md5(key: &[u8], message: &[u8]) -> [u8; MD5_LENGTH] {
+  hmac::<Md5>(key, message).try_into().unwrap()
+}
+
+/// How `verify_mac` keeps its timing from giving away how much of a MAC was
+/// right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MacCheck {
+  /// Compares every byte, however early they differ.
+  ConstantTime,
+  /// HMACs both MACs again under a fresh key and compares those, so where
+  /// they differ says nothing about the MAC that was sent.
+  DoubleHmac,
+}
+
+/// Checks that `mac` is the HMAC of `message` under `key`.
+pub fn verify_mac<H: Hasher>(key: &[u8], message: &[u8], mac: &[u8], check: MacCheck) -> bool {
+  let expected = hmac::<H>(key, message);
+  match check {
+    MacCheck::ConstantTime => ct_eq(&expected, mac),
+    MacCheck::DoubleHmac => {
+      let key = random_16();
+      hmac::<H>(&key, &expected) == hmac::<H>(&key, mac)
+    }
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn tea_zero_vector() {
+    let cipher = tea(&[0u8; 16]).unwrap();
+    assert_eq!(cipher.encrypt_block(&[0u8; 8]).to_hex(), "41ea3a0a94baa940");
+    assert_eq!(
+      cipher.decrypt_block(&"41ea3a0a94baa940".from_hex().unwrap()),
+      vec![0u8; 8]
+    );
+  }
+
+  #[test]
+  fn modes_with_8_byte_blocks() {
+    let cipher = tea(b"YELLOW SUBMARINE").unwrap();
+    let pt = b"Burning 'em, if you ain't quick and nimble";
+
+    let ct = encrypt_ecb_with(&cipher, pt);
+    assert_eq!(ct.len(), 48);
+    assert_eq!(decrypt_ecb_with(&cipher, &ct).unwrap(), pt);
+
+    let iv = [7u8; 8];
+    let ct = encrypt_cbc_with(&cipher, &iv, pt).unwrap();
+    assert_eq!(ct.len(), 48);
+    assert_eq!(decrypt_cbc_with(&cipher, &iv, &ct).unwrap(), pt);
+
+    let nonce = [1u8; 4];
+    let ct = encrypt_ctr_with(&cipher, &nonce, pt).unwrap();
+    assert_eq!(ct.len(), pt.len());
+    assert_eq!(decrypt_ctr_with(&cipher, &nonce, &ct).unwrap(), pt);
+  }
+
+  #[test]
+  fn bad_lengths_are_errors() {
+    assert_eq!(aes(&[0u8; 20]).err(), Some(Error::BadKeyLength));
+    assert_eq!(tea(&[0u8; 8]).err(), Some(Error::BadKeyLength));
+    assert_eq!(
+      encrypt_ecb(b"YELLOW SUBMARIN", b"pt"),
+      Err(Error::BadKeyLength)
+    );
+
+    let key = b"YELLOW SUBMARINE";
+    assert_eq!(encrypt_cbc(key, &[0u8; 8], b"pt"), Err(Error::BadLength));
+    assert_eq!(
+      decrypt_cbc(key, &[0u8; 16], &[0u8; 17]),
+      Err(Error::BadLength)
+    );
+    assert_eq!(decrypt_ecb(key, &[0u8; 15]), Err(Error::BadLength));
+    assert_eq!(decrypt_ecb(key, &[]), Err(Error::BadPadding));
+    assert_eq!(
+      encrypt_ctr_with(&aes(key).unwrap(), &[0u8; 16], b"pt"),
+      Err(Error::BadLength)
+    );
+  }
+
+  // NIST SP 800-38A, appendix F
+  const NIST_PT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+  const NIST_IV: &str = "000102030405060708090a0b0c0d0e0f";
+  const NIST_KEY_128: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+  const NIST_KEY_192: &str = "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b";
+  const NIST_KEY_256: &str = "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4";
+
+  fn check_nist_ecb(key: &str, ct: &str) {
+    let (key, pt) = (key.from_hex().unwrap(), NIST_PT.from_hex().unwrap());
+    let cipher = aes(&key).unwrap();
+    let found: Vec<u8> = pt
+      .chunks(16)
+      .flat_map(|b| cipher.encrypt_block(b))
+      .collect();
+    assert_eq!(found.to_hex(), ct);
+    let found: Vec<u8> = found
+      .chunks(16)
+      .flat_map(|b| cipher.decrypt_block(b))
+      .collect();
+    assert_eq!(found, pt);
+
+    // The padded mode adds a full block of padding after the vectors
+    let found = encrypt_ecb(&key, &pt).unwrap();
+    assert_eq!(found[..64].to_hex(), ct);
+    assert_eq!(decrypt_ecb(&key, &found).unwrap(), pt);
+  }
+
+  fn check_nist_cbc(key: &str, ct: &str) {
+    let (key, iv, pt) = (
+      key.from_hex().unwrap(),
+      NIST_IV.from_hex().unwrap(),
+      NIST_PT.from_hex().unwrap(),
+    );
+    let found = encrypt_cbc(&key, &iv, &pt).unwrap();
+    assert_eq!(found[..64].to_hex(), ct);
+    assert_eq!(decrypt_cbc(&key, &iv, &found).unwrap(), pt);
+  }
+
+  #[test]
+  fn nist_ecb_aes128() {
+    check_nist_ecb(NIST_KEY_128, "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4");
+  }
+
+  #[test]
+  fn nist_ecb_aes192() {
+    check_nist_ecb(NIST_KEY_192, "bd334f1d6e45f25ff712a214571fa5cc974104846d0ad3ad7734ecb3ecee4eefef7afd2270e2e60adce0ba2face6444e9a4b41ba738d6c72fb16691603c18e0e");
+  }
+
+  #[test]
+  fn nist_ecb_aes256() {
+    check_nist_ecb(NIST_KEY_256, "f3eed1bdb5d2a03c064b5a7e3db181f8591ccb10d410ed26dc5ba74a31362870b6ed21b99ca6f4f9f153e7b1beafed1d23304b7a39f9f3ff067d8d8f9e24ecc7");
+  }
+
+  #[test]
+  fn nist_cbc_aes128() {
+    check_nist_cbc(NIST_KEY_128, "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b273bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7");
+  }
+
+  #[test]
+  fn nist_cbc_aes192() {
+    check_nist_cbc(NIST_KEY_192, "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd");
+  }
+
+  #[test]
+  fn nist_cbc_aes256() {
+    check_nist_cbc(NIST_KEY_256, "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b");
+  }
+
+  #[test]
+  fn ctr_with_every_key_size() {
+    let pt = NIST_PT.from_hex().unwrap();
+    let nonce = [3u8; 8];
+    for key in &[NIST_KEY_128, NIST_KEY_192, NIST_KEY_256] {
+      let key = key.from_hex().unwrap();
+      let ct = encrypt_ctr(&key, &nonce, &pt).unwrap();
+      assert_eq!(
+        xor(&ct, &pt)[..16],
+        aes(&key)
+          .unwrap()
+          .encrypt_block(&[[3u8; 8], [0u8; 8]].concat())[..]
+      );
+      assert_eq!(decrypt_ctr(&key, &nonce, &ct).unwrap(), pt);
+    }
+  }
+
+  #[test]
+  fn sha1_vectors() {
+    assert_eq!(
+      sha1(b"").to_hex(),
+      "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+    );
+    assert_eq!(
+      sha1(b"abc").to_hex(),
+      "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    assert_eq!(
+      sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_hex(),
+      "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    );
+  }
+
+  #[test]
+  fn sha1_incremental() {
+    let mut hasher = Sha1::new();
+    for _ in 0..1000 {
+      hasher.update(&[b'a'; 1000]);
+    }
+    assert_eq!(
+      hasher.finalize().to_hex(),
+      "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+    );
+
+    let message = b"The quick brown fox jumps over the lazy dog, again and again and again";
+    for split in 0..message.len() {
+      let mut hasher = Sha1::new();
+      hasher.update(&message[..split]);
+      hasher.update(&message[split..]);
+      assert_eq!(hasher.finalize(), sha1(message));
+    }
+  }
+
+  #[test]
+  fn sha1_resume_from_digest() {
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let suffix = b";admin=true";
+    let glued = [&message[..], &Sha1::padding(message.len()), suffix].concat();
+
+    let mut hasher = Sha1::from_digest(&sha1(message), glued.len() - suffix.len());
+    hasher.update(suffix);
+    assert_eq!(hasher.finalize(), sha1(&glued));
+  }
+
+  #[test]
+  fn sha256_vectors() {
+    assert_eq!(
+      sha256(b"").to_hex(),
+      "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+      sha256(b"abc").to_hex(),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+      sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_hex(),
+      "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+
+    let mut hasher = Sha256::new();
+    for _ in 0..1000 {
+      hasher.update(&[b'a'; 1000]);
+    }
+    assert_eq!(
+      hasher.finalize().to_hex(),
+      "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+  }
+
+  #[test]
+  fn sha224_vectors() {
+    assert_eq!(
+      sha224(b"").to_hex(),
+      "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"
+    );
+    assert_eq!(
+      sha224(b"abc").to_hex(),
+      "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+    );
+  }
+
+  #[test]
+  fn sha256_extend_matches_glued_message() {
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let suffix = b";admin=true";
+    let glued = [&message[..], &Sha256::padding(message.len()), suffix].concat();
+
+    let digest = sha256(message);
+    let mut initial = [0u32; 8];
+    for (i, word) in digest.chunks(4).enumerate() {
+      initial[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    assert_eq!(
+      sha256_extend(suffix, &initial, glued.len() - suffix.len()),
+      sha256(&glued)
+    );
+  }
+
+  #[test]
+  fn md4_vectors() {
+    // RFC 1320, appendix A.5
+    assert_eq!(md4(b"").to_hex(), "31d6cfe0d16ae931b73c59d7e0c089c0");
+    assert_eq!(md4(b"a").to_hex(), "bde52cb31de33e46245e05fbdbd6fb24");
+    assert_eq!(md4(b"abc").to_hex(), "a448017aaf21d8525fc10ae87aa6729d");
+    assert_eq!(
+      md4(b"message digest").to_hex(),
+      "d9130a8164549fe818874806e1c7014b"
+    );
+    assert_eq!(
+      md4(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890")
+        .to_hex(),
+      "e33b4ddc9c38f2199c3e7b164fcc0536"
+    );
+  }
+
+  #[test]
+  fn md4_extend_matches_glued_message() {
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let suffix = b";admin=true";
+    let glued = [&message[..], &Md4::padding(message.len()), suffix].concat();
+
+    let digest = md4(message);
+    let mut initial = [0u32; 4];
+    for (i, word) in digest.chunks(4).enumerate() {
+      initial[i] = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    assert_eq!(
+      md4_extend(suffix, &initial, glued.len() - suffix.len()),
+      md4(&glued)
+    );
+  }
+
+  #[test]
+  fn md4_round1_inverts() {
+    let mut m = [0u32; 16];
+    words_le(
+      b"Wang's attack works on one 64 byte block of MD4 at a time, yes!!",
+      &mut m,
+    );
+    let q = md4_steps(&MD4_INITIAL, &m);
+    for (i, &word) in m.iter().enumerate() {
+      assert_eq!(md4_round1_word(&q, i), word);
+    }
+  }
+
+  // RFC 2202 HMAC-SHA1 test cases 1, 2, 3, 6 and 7
+  const HMAC_CASES: [(&[u8], &[u8]); 5] = [
+    (&[0x0b; 20], b"Hi There"),
+    (b"Jefe", b"what do ya want for nothing?"),
+    (&[0xaa; 20], &[0xdd; 50]),
+    (
+      &[0xaa; 80],
+      b"Test Using Larger Than Block-Size Key - Hash Key First",
+    ),
+    (
+      &[0xaa; 80],
+      b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data",
+    ),
+  ];
+
+  #[test]
+  fn hmac_sha1_rfc2202() {
+    let expected = [
+      "b617318655057264e28bc0b6fb378c8ef146be00",
+      "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+      "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+      "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+      "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+    ];
+    for ((key, data), mac) in HMAC_CASES.iter().zip(&expected) {
+      assert_eq!(hmac_sha1(key, data).to_hex(), *mac);
+    }
+  }
+
+  #[test]
+  fn hmac_md5_rfc2202() {
+    let cases: [(&[u8], &[u8], &str); 5] = [
+      (&[0x0b; 16], b"Hi There", "9294727a3638bb1c13f48ef8158bfc9d"),
+      (
+        b"Jefe",
+        b"what do ya want for nothing?",
+        "750c783e6ab0b503eaa86e310a5db738",
+      ),
+      (&[0xaa; 16], &[0xdd; 50], "56be34521d144c88dbb8c733f0e8b3f6"),
+      (
+        HMAC_CASES[3].0,
+        HMAC_CASES[3].1,
+        "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+      ),
+      (
+        HMAC_CASES[4].0,
+        HMAC_CASES[4].1,
+        "6f630fad67cda0ee1fb1f562db3aa53e",
+      ),
+    ];
+    for (key, data, mac) in cases.iter() {
+      assert_eq!(hmac_md5(key, data).to_hex(), *mac);
+    }
+  }
+
+  #[test]
+  fn hmac_sha256_rfc4231() {
+    let long_key = [0xaa; 131];
+    let cases: [(&[u8], &[u8], &str); 5] = [
+      (
+        &[0x0b; 20],
+        b"Hi There",
+        "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+      ),
+      (
+        b"Jefe",
+        b"what do ya want for nothing?",
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+      ),
+      (
+        &[0xaa; 20],
+        &[0xdd; 50],
+        "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+      ),
+      (
+        &long_key,
+        b"Test Using Larger Than Block-Size Key - Hash Key First",
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+      ),
+      (
+        &long_key,
+        b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+      ),
+    ];
+    for (key, data, mac) in cases.iter() {
+      assert_eq!(hmac_sha256(key, data).to_hex(), *mac);
+    }
+
+    assert_eq!(
+      hmac::<Sha224>(b"Jefe", b"what do ya want for nothing?").to_hex(),
+      "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44"
+    );
+  }
+
+  #[test]
+  fn verify_mac_either_way() {
+    let mac = hmac_sha1(b"key", b"message");
+    let mut wrong = mac;
+    wrong[19] ^= 1;
+    for &check in &[MacCheck::ConstantTime, MacCheck::DoubleHmac] {
+      assert!(verify_mac::<Sha1>(b"key", b"message", &mac, check));
+      assert!(!verify_mac::<Sha1>(b"key", b"message", &wrong, check));
+      assert!(!verify_mac::<Sha1>(b"key", b"message", &mac[..19], check));
+      assert!(!verify_mac::<Sha1>(b"yek", b"message", &mac, check));
+    }
+  }
+
   #[test]
   fn md5_fox() {
     assert_eq!(
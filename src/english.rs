@@ -1,5 +1,7 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+//! Scoring how much a candidate plaintext looks like English.
+
+use crate::language::{LanguageModel, Scoring};
+use crate::wordlist;
 
 fn english_counts(byte: u8) -> i64 {
   // Taken from https://link.springer.com/content/pdf/10.3758%2FBF03195586.pdf
@@ -92,47 +94,78 @@ fn english_counts(byte: u8) -> i64 {
   }
 }
 
-//fn frequency_vector(bytes: &Vec<u8>) -> Vec<f
-
 pub fn score(bytes: &Vec<u8>) -> f64 {
-  // Collect byte/letter frequencies
-  let mut freq: [u64; 256] = [0; 256];
-  for &b in bytes.iter() {
-    freq[b as usize] += 1;
+  score_with(bytes, Scoring::Unigram)
+}
 
-    // Hack to fix challenge 20
-    if b == b'~' {
-      return 0f64;
-    }
+/// Scores with byte frequencies from `letters()`, or with `ngrams()` for
+/// n-gram scoring.
+pub fn score_with(bytes: &[u8], scoring: Scoring) -> f64 {
+  match scoring {
+    Scoring::NGram => ngrams().score(bytes, scoring),
+    _ => letters().score(bytes, scoring),
   }
+}
 
-  // Get total sample counts
-  let total = (0u8..=255).map(|c| english_counts(c)).fold(0, |a, b| a + b);
-
-  // Calculate distance from English frequency vector
-  let mut sum_dist_squared = 0f64;
-  for b in 0..=255 {
-    sum_dist_squared += (english_counts(b) as f64 / total as f64
-      - (freq[b as usize] as f64) / (bytes.len() as f64))
-      .powi(2)
-  }
-  let distance = sum_dist_squared.sqrt();
+/// Byte frequencies of English, from `english_counts`.
+pub fn letters() -> &'static LanguageModel {
+  &*LETTERS
+}
 
-  // Score
-  1.0 - distance
+/// Quadgrams of the song lyrics in `data/`.
+pub fn ngrams() -> &'static LanguageModel {
+  &*NGRAMS
 }
 
+/// The first word list `wordlist::search_paths()` turns up, or the embedded
+/// one.
 pub fn words() -> &'static Vec<String> {
   &*WORDS
 }
 
+/// The lyrics `ngrams()` learns from, built in so it works from anywhere.
+const CORPUS: [&[u8]; 2] = [
+  include_bytes!("../data/play-that-funky-music.txt"),
+  include_bytes!("../data/ice-ice-baby.txt"),
+];
+
 lazy_static! {
-  static ref WORDS: Vec<String> = {
-    let file = File::open("/usr/share/dict/words").expect("Failed to open dict");
-    let words: Vec<_> = BufReader::new(file)
-      .lines()
-      .map(|l| l.unwrap().clone())
-      .collect();
-    words
+  static ref LETTERS: LanguageModel = {
+    let mut counts = [0; 256];
+    for b in 0..=255 {
+      counts[b as usize] = english_counts(b) as u64;
+    }
+    LanguageModel::from_byte_counts(&counts)
   };
+  static ref NGRAMS: LanguageModel = LanguageModel::train(&CORPUS.join(&b'\n'), 4);
+  static ref WORDS: Vec<String> = wordlist::load_first(&wordlist::search_paths());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ENGLISH: &[u8] = b"Now that the party is jumping with the bass kicked in";
+
+  #[test]
+  fn every_scoring_prefers_english() {
+    let garbled: Vec<_> = ENGLISH.iter().map(|b| b ^ 0x55).collect();
+    for &scoring in &[Scoring::Unigram, Scoring::ChiSquared, Scoring::NGram] {
+      assert!(score_with(ENGLISH, scoring) > score_with(&garbled, scoring));
+    }
+  }
+
+  #[test]
+  fn chi_squared_penalizes_rare_bytes() {
+    let tilde = [&ENGLISH[..ENGLISH.len() - 1], b"~"].concat();
+    assert!(score_with(ENGLISH, Scoring::ChiSquared) > score_with(&tilde, Scoring::ChiSquared));
+  }
+
+  #[test]
+  fn ngrams_know_how_lines_start() {
+    let model = ngrams();
+    assert!(model.log_prob(b"", b'Y') > model.log_prob(b"", b'y'));
+    assert!(model.log_prob(b"ic", b'e') > model.log_prob(b"ic", b'q'));
+    assert!(model.log_prob(b"", 0) < 0.0);
+  }
 }
@@ -0,0 +1,32 @@
+//! The error type shared by the decoding and cipher helpers.
+
+use std::fmt;
+
+/// The ways decoding or decrypting untrusted input can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+  InvalidHex,
+  InvalidBase64,
+  BadPadding,
+  BadKeyLength,
+  BadLength,
+  NotUtf8,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let message = match self {
+      Error::InvalidHex => "Invalid hex string",
+      Error::InvalidBase64 => "Invalid Base64 string",
+      Error::BadPadding => "Invalid padding",
+      Error::BadKeyLength => "Invalid key length",
+      Error::BadLength => "Invalid length",
+      Error::NotUtf8 => "Invalid UTF-8",
+    };
+    f.write_str(message)
+  }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,142 @@
+//! Linear equations over GF(2), where adding is XOR, for generators whose
+//! outputs are linear in their state bits.
+
+/// A row of coefficients, 64 unknowns to a word, lowest first.
+pub type Row = Vec<u64>;
+
+pub fn row(unknowns: usize) -> Row {
+  vec![0; unknowns.div_ceil(64)]
+}
+
+pub fn get(row: &[u64], i: usize) -> bool {
+  row[i / 64] >> (i % 64) & 1 == 1
+}
+
+pub fn flip(row: &mut [u64], i: usize) {
+  row[i / 64] ^= 1 << (i % 64);
+}
+
+pub fn add(row: &mut [u64], other: &[u64]) {
+  for (a, b) in row.iter_mut().zip(other) {
+    *a ^= b;
+  }
+}
+
+/// Equations added one at a time, kept in echelon form: each one is reduced
+/// by those before it until its lowest unknown is one nothing else starts
+/// with.
+#[derive(Clone, Debug)]
+pub struct System {
+  unknowns: usize,
+  pivots: Vec<Option<(Row, bool)>>,
+  rank: usize,
+  consistent: bool,
+}
+
+impl System {
+  pub fn new(unknowns: usize) -> Self {
+    System {
+      unknowns,
+      pivots: vec![None; unknowns],
+      rank: 0,
+      consistent: true,
+    }
+  }
+
+  /// Adds `row · x = value`, returning whether it told us anything new.
+  pub fn add(&mut self, mut row: Row, mut value: bool) -> bool {
+    let mut word = 0;
+    while word < row.len() {
+      if row[word] == 0 {
+        word += 1;
+        continue;
+      }
+      let i = word * 64 + row[word].trailing_zeros() as usize;
+      match &self.pivots[i] {
+        Some((pivot, pivot_value)) => {
+          add(&mut row, pivot);
+          value ^= pivot_value;
+        }
+        None => {
+          self.pivots[i] = Some((row, value));
+          self.rank += 1;
+          return true;
+        }
+      }
+    }
+    // Everything cancelled, so it had better have been 0 = 0
+    self.consistent &= !value;
+    false
+  }
+
+  pub fn rank(&self) -> usize {
+    self.rank
+  }
+
+  /// A solution, with every unknown nothing pinned down set to 0, or `None`
+  /// when the equations contradict each other.
+  pub fn solve(&self) -> Option<Row> {
+    if !self.consistent {
+      return None;
+    }
+    let mut x = row(self.unknowns);
+    for i in (0..self.unknowns).rev() {
+      if let Some((pivot, value)) = &self.pivots[i] {
+        let parity = pivot
+          .iter()
+          .zip(&x)
+          .fold(0, |parity, (a, b)| parity ^ (a & b).count_ones());
+        if (parity % 2 == 1) != *value {
+          flip(&mut x, i);
+        }
+      }
+    }
+    Some(x)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::prelude::*;
+
+  #[test]
+  fn solves_random_systems() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let unknowns = 200;
+    let mut x = row(unknowns);
+    for i in 0..unknowns {
+      if rng.gen() {
+        flip(&mut x, i);
+      }
+    }
+
+    let mut system = System::new(unknowns);
+    while system.rank() < unknowns {
+      let mut equation = row(unknowns);
+      for i in 0..unknowns {
+        if rng.gen() {
+          flip(&mut equation, i);
+        }
+      }
+      let value = (0..unknowns)
+        .filter(|&i| get(&equation, i) && get(&x, i))
+        .count()
+        % 2
+        == 1;
+      system.add(equation, value);
+    }
+    assert_eq!(system.solve(), Some(x));
+  }
+
+  #[test]
+  fn spots_contradictions() {
+    let mut system = System::new(2);
+    assert!(system.add(vec![0b11], true));
+    assert!(system.add(vec![0b01], true));
+    assert!(!system.add(vec![0b10], false));
+    assert_eq!(system.solve(), Some(vec![0b01]));
+    assert!(!system.add(vec![0b10], true));
+    assert_eq!(system.solve(), None);
+  }
+}
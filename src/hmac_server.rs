@@ -0,0 +1,172 @@
+//! A server that checks HMAC-SHA1 signatures on files, for timing attacks to
+//! practice on. `/hmac/{file}/{signature}` answers 200 when `signature` is
+//! the hex HMAC of `file` and 500 when it isn't.
+
+use crate::bytes::*;
+use crate::crypt::{hmac_sha1, verify_mac, MacCheck, Sha1, SHA1_LENGTH};
+use actix_web::dev::Server;
+use actix_web::rt::System;
+use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// How the server compares the signature it was sent with the right one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+  /// Byte by byte, stopping at the first difference.
+  EarlyExit,
+  /// With `verify_mac`, comparing every byte.
+  ConstantTime,
+  /// With `verify_mac`, HMACing both again first.
+  DoubleHmac,
+}
+
+impl FromStr for Comparison {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "early-exit" => Ok(Comparison::EarlyExit),
+      "constant-time" => Ok(Comparison::ConstantTime),
+      "double-hmac" => Ok(Comparison::DoubleHmac),
+      _ => Err(format!("Unknown comparison {}", s)),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+  pub key: Vec<u8>,
+  /// How long comparing each byte takes.
+  pub delay: Duration,
+  /// Up to how much longer, at random, each check takes.
+  pub jitter: Duration,
+  pub comparison: Comparison,
+}
+
+impl Config {
+  /// The setup from challenge 31: a 50ms early-exit comparison.
+  pub fn new(key: &[u8]) -> Self {
+    Config {
+      key: key.to_vec(),
+      delay: Duration::from_millis(50),
+      jitter: Duration::from_millis(0),
+      comparison: Comparison::EarlyExit,
+    }
+  }
+}
+
+async fn slow_equals(a: &[u8], b: &[u8], delay: Duration) -> bool {
+  if a.len() != b.len() {
+    return false;
+  }
+  for i in 0..a.len() {
+    sleep(delay).await;
+    if a[i] != b[i] {
+      return false;
+    }
+  }
+  true
+}
+
+async fn check(config: &Config, file: &str, signature: &str) -> bool {
+  let signature = match signature.from_hex() {
+    Ok(signature) => signature,
+    Err(_) => return false,
+  };
+  sleep(config.jitter.mul_f64(rand::random())).await;
+
+  let check = match config.comparison {
+    Comparison::EarlyExit => {
+      let hmac = hmac_sha1(&config.key, file.as_bytes());
+      return slow_equals(&hmac, &signature, config.delay).await;
+    }
+    Comparison::ConstantTime => MacCheck::ConstantTime,
+    Comparison::DoubleHmac => MacCheck::DoubleHmac,
+  };
+  // As slow as the early exit comparison at its slowest, every time
+  sleep(config.delay * SHA1_LENGTH as u32).await;
+  verify_mac::<Sha1>(&config.key, file.as_bytes(), &signature, check)
+}
+
+#[get("/hmac/{file}/{signature}")]
+async fn verify(config: web::Data<Config>, path: web::Path<(String, String)>) -> impl Responder {
+  let (file, signature) = path.into_inner();
+  if check(&config, &file, &signature).await {
+    HttpResponse::Ok()
+  } else {
+    HttpResponse::InternalServerError()
+  }
+}
+
+/// Starts serving on `addr` from a thread of its own, so it can be started
+/// from anywhere. Binding to port 0 picks a free port, which is why the
+/// address actually listened on is returned with the handle to stop it.
+pub fn start<A>(addr: A, config: Config) -> io::Result<(Server, SocketAddr)>
+where
+  A: ToSocketAddrs + Send + 'static,
+{
+  let (started, starting) = mpsc::channel();
+  thread::spawn(move || {
+    let system = System::new();
+    let config = web::Data::new(config);
+    let server = HttpServer::new(move || App::new().app_data(config.clone()).service(verify))
+      .disable_signals()
+      .bind(addr);
+    match server {
+      Ok(server) => {
+        let addr = server.addrs()[0];
+        system.block_on(async {
+          let server = server.run();
+          started.send(Ok((server.clone(), addr))).unwrap();
+          server.await.ok();
+        });
+      }
+      Err(err) => started.send(Err(err)).unwrap(),
+    }
+  });
+  starting.recv().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn status(addr: SocketAddr, file: &str, signature: &[u8]) -> u16 {
+    let url = format!("http://{}/hmac/{}/{}", addr, file, signature.to_hex());
+    reqwest::get(url).await.unwrap().status().as_u16()
+  }
+
+  #[test]
+  fn ephemeral_servers_dont_collide() {
+    let key = random_16();
+    let mut servers = Vec::new();
+    for &comparison in &[
+      Comparison::EarlyExit,
+      Comparison::ConstantTime,
+      Comparison::DoubleHmac,
+    ] {
+      let config = Config {
+        delay: Duration::from_millis(0),
+        comparison,
+        ..Config::new(&key)
+      };
+      servers.push(start("127.0.0.1:0", config).unwrap());
+    }
+
+    let hmac = hmac_sha1(&key, b"file");
+    System::new().block_on(async {
+      for (server, addr) in servers {
+        assert_eq!(status(addr, "file", &hmac).await, 200);
+        assert_eq!(status(addr, "file", &hmac[..19]).await, 500);
+        assert_eq!(status(addr, "other", &hmac).await, 500);
+        server.stop(true).await;
+      }
+    });
+  }
+}
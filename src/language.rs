@@ -0,0 +1,315 @@
+//! Byte n-gram language models, for telling when a candidate plaintext
+//! looks like whatever the plaintext should be: English, another language,
+//! JSON or logs.
+
+use crate::bytes::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// How `LanguageModel::score` judges a candidate plaintext. Higher scores
+/// look more like the model's language whichever is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+  /// Closeness of the byte frequencies to the model's.
+  Unigram,
+  /// Pearson's chi-squared test of the byte counts against the model's,
+  /// which comes down hard on bytes the language hardly ever uses.
+  ChiSquared,
+  /// Log-likelihood under the model's n-grams.
+  NGram,
+}
+
+/// Stands in for what comes before the start of a line.
+const LINE_START: u8 = b'\n';
+/// Discount for each step of backing off to a shorter n-gram.
+const BACKOFF: f64 = 0.4;
+/// How often bytes the model never saw are expected by the chi-squared test,
+/// or any text with one in it would be infinitely unlikely.
+const FLOOR: f64 = 1e-6;
+
+/// Byte n-gram counts, for the log-likelihood of text under them. Unseen
+/// n-grams back off to shorter ones, down to single bytes, which are
+/// smoothed so nothing is impossible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageModel {
+  n: usize,
+  counts: HashMap<Vec<u8>, u64>,
+  total: u64,
+  /// How many of the single `LINE_START`s counted were padding rather than
+  /// bytes trained on.
+  padding: u64,
+}
+
+/// What a model is saved as. JSON maps need string keys, so grams are hex.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+  n: usize,
+  total: u64,
+  #[serde(default)]
+  padding: u64,
+  counts: BTreeMap<String, u64>,
+}
+
+impl LanguageModel {
+  /// Counts every gram of up to `n` bytes in the lines of `text`.
+  pub fn train(text: &[u8], n: usize) -> Self {
+    assert!(n > 0, "n-grams need at least one byte");
+    let mut counts = HashMap::new();
+    let mut total = 0;
+    for line in text.split(|&b| b == b'\n') {
+      let padded = [&vec![LINE_START; n - 1], line].concat();
+      // Grams within the padding are counted too, as the context for how
+      // lines start
+      for end in 0..padded.len() {
+        for length in 1..=n.min(end + 1) {
+          *counts
+            .entry(padded[end + 1 - length..=end].to_vec())
+            .or_insert(0) += 1;
+        }
+      }
+      total += line.len() as u64;
+    }
+    let lines = text.split(|&b| b == b'\n').count() as u64;
+    LanguageModel {
+      n,
+      counts,
+      total,
+      padding: (n as u64 - 1) * lines,
+    }
+  }
+
+  /// Trains on the files at `paths`, one after the other.
+  pub fn train_files<P: AsRef<Path>>(paths: &[P], n: usize) -> io::Result<Self> {
+    let mut corpus = Vec::new();
+    for path in paths {
+      if !corpus.is_empty() {
+        corpus.push(b'\n');
+      }
+      corpus.extend(fs::read(path)?);
+    }
+    Ok(LanguageModel::train(&corpus, n))
+  }
+
+  /// A model of single bytes from how often each turns up, for when only
+  /// the frequencies are known.
+  pub fn from_byte_counts(counts: &[u64; 256]) -> Self {
+    LanguageModel {
+      n: 1,
+      counts: (0..=255)
+        .filter(|&b| counts[b as usize] > 0)
+        .map(|b| (vec![b], counts[b as usize]))
+        .collect(),
+      total: counts.iter().sum(),
+      padding: 0,
+    }
+  }
+
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+    let saved = Saved {
+      n: self.n,
+      total: self.total,
+      padding: self.padding,
+      counts: self
+        .counts
+        .iter()
+        .map(|(gram, &count)| (to_hex(gram), count))
+        .collect(),
+    };
+    fs::write(path, serde_json::to_vec(&saved)?)
+  }
+
+  pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    let invalid = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+    let saved: Saved = serde_json::from_slice(&fs::read(path)?)?;
+    if saved.n == 0 {
+      return Err(invalid("n-grams need at least one byte".to_string()));
+    }
+    let mut counts = HashMap::new();
+    for (gram, count) in saved.counts {
+      let gram = from_hex(&gram).map_err(|err| invalid(err.to_string()))?;
+      if gram.is_empty() || gram.len() > saved.n || count == 0 {
+        return Err(invalid(format!("Bad count for gram {}", to_hex(&gram))));
+      }
+      counts.insert(gram, count);
+    }
+    // Every byte trained on was counted once as a single byte, and so was
+    // the padding before each line
+    let singles: u64 = counts
+      .iter()
+      .filter(|(gram, _)| gram.len() == 1)
+      .map(|(_, &count)| count)
+      .sum();
+    let line_starts = counts.get(&vec![LINE_START]).copied().unwrap_or(0);
+    if singles != saved.total + saved.padding || saved.padding > line_starts {
+      return Err(invalid("Bytes counted don't add up".to_string()));
+    }
+    Ok(LanguageModel {
+      n: saved.n,
+      counts,
+      total: saved.total,
+      padding: saved.padding,
+    })
+  }
+
+  /// The longest grams counted.
+  pub fn n(&self) -> usize {
+    self.n
+  }
+
+  /// How much of the text trained on `byte` made up.
+  pub fn frequency(&self, byte: u8) -> f64 {
+    self.count(byte) as f64 / self.total.max(1) as f64
+  }
+
+  /// How many times `byte` turned up in the text trained on, leaving out
+  /// padding.
+  fn count(&self, byte: u8) -> u64 {
+    let count = self.counts.get(&vec![byte]).copied().unwrap_or(0);
+    if byte == LINE_START {
+      count - self.padding
+    } else {
+      count
+    }
+  }
+
+  /// Log-probability of `byte` coming after `before`. Only the last `n - 1`
+  /// bytes of `before` matter, and a short `before` is the start of a line.
+  pub fn log_prob(&self, before: &[u8], byte: u8) -> f64 {
+    let padding = (self.n - 1).saturating_sub(before.len());
+    let context = [
+      &vec![LINE_START; padding],
+      &before[before.len() - (self.n - 1 - padding)..],
+    ]
+    .concat();
+
+    let mut discount = 1.0;
+    for start in 0..context.len() {
+      let gram = [&context[start..], &[byte]].concat();
+      if let (Some(&count), Some(&seen)) =
+        (self.counts.get(&gram), self.counts.get(&context[start..]))
+      {
+        return (discount * count as f64 / seen as f64).ln();
+      }
+      discount *= BACKOFF;
+    }
+    let count = self.count(byte);
+    (discount * (count + 1) as f64 / (self.total + 256) as f64).ln()
+  }
+
+  pub fn score(&self, bytes: &[u8], scoring: Scoring) -> f64 {
+    match scoring {
+      Scoring::Unigram => self.unigram(bytes),
+      Scoring::ChiSquared => -self.chi_squared(bytes),
+      Scoring::NGram => self.mean_log_prob(bytes),
+    }
+  }
+
+  fn unigram(&self, bytes: &[u8]) -> f64 {
+    let freq = byte_counts(bytes);
+    let distance = (0..=255)
+      .map(|b| (self.frequency(b) - freq[b as usize] as f64 / bytes.len() as f64).powi(2))
+      .sum::<f64>()
+      .sqrt();
+    1.0 - distance
+  }
+
+  fn chi_squared(&self, bytes: &[u8]) -> f64 {
+    let freq = byte_counts(bytes);
+    (0..=255)
+      .map(|b| {
+        let expected = self.frequency(b).max(FLOOR) * bytes.len() as f64;
+        (freq[b as usize] as f64 - expected).powi(2) / expected
+      })
+      .sum()
+  }
+
+  /// Mean log-probability per byte of `bytes` as a line of text.
+  fn mean_log_prob(&self, bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+      return 0.0;
+    }
+    let total: f64 = (0..bytes.len())
+      .map(|i| self.log_prob(&bytes[..i], bytes[i]))
+      .sum();
+    total / bytes.len() as f64
+  }
+}
+
+fn byte_counts(bytes: &[u8]) -> [u64; 256] {
+  let mut freq = [0; 256];
+  for &b in bytes {
+    freq[b as usize] += 1;
+  }
+  freq
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::env;
+
+  const LOG: &[u8] = b"GET /index.html 200\nGET /about.html 200\nPOST /login 302\n\
+    GET /missing.html 404\nPOST /login 200\nGET /index.html 304";
+
+  #[test]
+  fn saved_models_load_the_same() {
+    let model = LanguageModel::train(LOG, 3);
+    let path = env::temp_dir().join(format!("model-{}.json", to_hex(&random_8())));
+    model.save(&path).unwrap();
+    let loaded = LanguageModel::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), model);
+  }
+
+  #[test]
+  fn padding_isnt_a_frequency() {
+    for n in 1..=4 {
+      let model = LanguageModel::train(LOG, n);
+      let sum: f64 = (0..=255).map(|b| model.frequency(b)).sum();
+      assert!((sum - 1.0).abs() < 1e-9, "{} sums to {}", n, sum);
+    }
+    assert_eq!(LanguageModel::train(b"ab\ncd", 4).frequency(b'\n'), 0.0);
+  }
+
+  #[test]
+  fn broken_models_dont_load() {
+    let path = env::temp_dir().join(format!("model-{}.json", to_hex(&random_8())));
+    for json in &[
+      r#"{"n":0,"total":1,"counts":{"61":1}}"#,
+      r#"{"n":1,"total":1,"counts":{"6161":1}}"#,
+      r#"{"n":2,"total":1,"counts":{"61":3}}"#,
+      r#"{"n":2,"total":1,"padding":1,"counts":{"61":2}}"#,
+    ] {
+      fs::write(&path, json).unwrap();
+      let err = LanguageModel::load(&path).unwrap_err();
+      assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", json);
+    }
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn models_prefer_what_they_were_trained_on() {
+    let model = LanguageModel::train(LOG, 3);
+    for &scoring in &[Scoring::Unigram, Scoring::ChiSquared, Scoring::NGram] {
+      assert!(
+        model.score(b"GET /login 404", scoring) > model.score(b"We're rolling", scoring),
+        "{:?}",
+        scoring
+      );
+    }
+  }
+
+  #[test]
+  fn byte_counts_are_unigrams() {
+    let mut counts = [0; 256];
+    counts[b'a' as usize] = 3;
+    counts[b'b' as usize] = 1;
+    let model = LanguageModel::from_byte_counts(&counts);
+    assert_eq!(model.frequency(b'a'), 0.75);
+    assert_eq!(model.frequency(b'c'), 0.0);
+    assert!(model.log_prob(b"b", b'a') > model.log_prob(b"a", b'b'));
+  }
+}
@@ -0,0 +1,22 @@
+//! The building blocks behind the [cryptopals](https://cryptopals.com)
+//! solutions: byte wrangling, ciphers and hashes, language models, the
+//! Mersenne Twister and the attacks that tie them together.
+//!
+//! The challenges themselves live in the `cryptopals-rust` binary, which
+//! only uses what is exported here.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod bytes;
+pub mod crack;
+pub mod crypt;
+pub mod english;
+pub mod error;
+pub mod gf2;
+pub mod hmac_server;
+pub mod language;
+pub mod padding_oracle_server;
+pub mod rand;
+pub mod timing;
+pub mod wordlist;
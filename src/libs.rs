@@ -1,4 +0,0 @@
-mod bytes;
-mod crack;
-mod crypt;
-mod english;
@@ -1,10 +1,12 @@
-use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use clap::Arg;
+use serde::Serialize;
 use tokio::task;
 
 #[macro_use]
 extern crate lazy_static;
 
-mod bytes;
+#[macro_use]
+mod challenge;
 mod challenge1;
 mod challenge10;
 mod challenge11;
@@ -46,95 +48,213 @@ mod challenge6;
 mod challenge7;
 mod challenge8;
 mod challenge9;
+mod padding_oracle;
+mod timing_oracle;
 
-mod crack;
-mod crypt;
-mod english;
-mod rand;
-
-use std::fs;
-
-async fn solve(server: actix_web::dev::Server) {
-  let _ = tokio::join!(
-    task::spawn(challenge1::solve()),
-    task::spawn(challenge2::solve()),
-    task::spawn(challenge3::solve()),
-    task::spawn(challenge4::solve()),
-    task::spawn(challenge5::solve()),
-    task::spawn(challenge6::solve(&VANILLA)),
-    task::spawn(challenge7::solve(&VANILLA)),
-    task::spawn(challenge8::solve()),
-    task::spawn(challenge9::solve()),
-    task::spawn(challenge10::solve(&VANILLA)),
-    task::spawn(challenge11::solve()),
-    task::spawn(challenge12::solve(&ICE_ICE_BABY)),
-    task::spawn(challenge13::solve()),
-    task::spawn(challenge14::solve(&ICE_ICE_BABY)),
-    task::spawn(challenge15::solve()),
-    task::spawn(challenge16::solve()),
-    task::spawn(challenge17::solve()),
-    task::spawn(challenge18::solve()),
-    task::spawn(challenge19::solve()),
-    task::spawn(challenge20::solve(&FURY)),
-    task::spawn(challenge21::solve()),
-    task::spawn(challenge22::solve()),
-    task::spawn(challenge23::solve()),
-    task::spawn(challenge24::solve()),
-    task::spawn(challenge25::solve()),
-    task::spawn(challenge26::solve()),
-    task::spawn(challenge27::solve()),
-    task::spawn(challenge28::solve()),
-    task::spawn(challenge29::solve()),
-    task::spawn(challenge30::solve()),
-    task::spawn(challenge31::solve()),
-    task::spawn(challenge32::solve()),
-    task::spawn(challenge33::solve()),
-    task::spawn(challenge34::solve()),
-    task::spawn(challenge35::solve()),
-    task::spawn(challenge36::solve()),
-    task::spawn(challenge37::solve()),
-    // slow
-    // task::spawn(challenge38::solve()),
-    task::spawn(challenge39::solve()),
-    task::spawn(challenge40::solve()),
-    task::spawn(challenge41::solve())
-  );
+use challenge::{Challenge, Outcome};
+use std::process;
+use std::time::Instant;
 
-  server.stop(false).await
+#[derive(Serialize)]
+struct Listing {
+  challenge: u8,
+  set: u8,
+  title: &'static str,
+  slow: bool,
 }
 
-#[actix_web::main]
-pub async fn main() {
-  #[get("/31-hmac/{file}/{signature}")]
-  async fn hmac(path: web::Path<(String, String)>) -> impl Responder {
-    let (file, signature) = path.into_inner();
-    if challenge31::check(file, signature).await {
-      HttpResponse::Ok()
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+  Passed,
+  Skipped,
+  Failed,
+}
+
+#[derive(Serialize)]
+struct Report {
+  challenge: u8,
+  set: u8,
+  title: &'static str,
+  status: Status,
+  seconds: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  message: Option<String>,
+}
+
+fn panic_message(error: task::JoinError) -> String {
+  if !error.is_panic() {
+    return error.to_string();
+  }
+  let panic = error.into_panic();
+  if let Some(message) = panic.downcast_ref::<String>() {
+    message.clone()
+  } else if let Some(message) = panic.downcast_ref::<&str>() {
+    message.to_string()
+  } else {
+    String::from("panicked")
+  }
+}
+
+/// Runs the challenges one at a time so the wall time of each is its own.
+/// Each runs in its own task, so one that panics is reported as failed
+/// rather than taking the runner down. Those `skip` picks out are reported
+/// as skipped without running.
+async fn run<F>(challenges: Vec<&'static dyn Challenge>, skip: F) -> Vec<Report>
+where
+  F: Fn(&dyn Challenge) -> bool,
+{
+  let mut reports = Vec::new();
+  for challenge in challenges {
+    let start = Instant::now();
+    let result = if skip(challenge) {
+      Ok(Ok(Outcome::Skipped("slow, run it with --slow")))
     } else {
-      HttpResponse::InternalServerError()
+      task::spawn(challenge.run()).await
+    };
+    let seconds = start.elapsed().as_secs_f64();
+    let (status, message) = match result {
+      Ok(Ok(Outcome::Solved)) => (Status::Passed, None),
+      Ok(Ok(Outcome::Skipped(reason))) => (Status::Skipped, Some(reason.to_string())),
+      Ok(Err(error)) => (Status::Failed, Some(error.to_string())),
+      Err(error) => (Status::Failed, Some(panic_message(error))),
+    };
+    reports.push(Report {
+      challenge: challenge.number(),
+      set: challenge.set(),
+      title: challenge.title(),
+      status,
+      seconds,
+      message,
+    });
+  }
+  reports
+}
+
+fn print_listing(challenges: &[&'static dyn Challenge], json: bool) {
+  if json {
+    let listing: Vec<_> = challenges
+      .iter()
+      .map(|c| Listing {
+        challenge: c.number(),
+        set: c.set(),
+        title: c.title(),
+        slow: c.slow(),
+      })
+      .collect();
+    println!("{}", serde_json::to_string_pretty(&listing).unwrap());
+  } else {
+    for c in challenges {
+      let slow = if c.slow() { " (slow)" } else { "" };
+      println!("{:>2}  set {}  {}{}", c.number(), c.set(), c.title(), slow);
     }
   }
+}
 
-  #[get("/32-hmac/{file}/{signature}")]
-  async fn hmac2(path: web::Path<(String, String)>) -> impl Responder {
-    let (file, signature) = path.into_inner();
-    if challenge32::check(file, signature).await {
-      HttpResponse::Ok()
-    } else {
-      HttpResponse::InternalServerError()
+fn print_reports(reports: &[Report], json: bool) {
+  if json {
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    return;
+  }
+
+  for report in reports {
+    let status = match report.status {
+      Status::Passed => "ok",
+      Status::Skipped => "skipped",
+      Status::Failed => "FAILED",
+    };
+    print!(
+      "{:>2}  set {}  {:<7} {:>9.3}s",
+      report.challenge, report.set, status, report.seconds
+    );
+    match &report.message {
+      Some(message) => println!("  {}", message.lines().next().unwrap_or("")),
+      None => println!(),
     }
   }
+  let count = |status| reports.iter().filter(|r| r.status == status).count();
+  println!(
+    "\n{} passed, {} skipped, {} failed",
+    count(Status::Passed),
+    count(Status::Skipped),
+    count(Status::Failed)
+  );
+}
 
-  let server = HttpServer::new(|| App::new().service(hmac).service(hmac2))
-    .bind("127.0.0.1:9000")
-    .unwrap()
-    .run();
+fn cli() -> clap::App<'static, 'static> {
+  let is_number = |value: String| {
+    value
+      .parse::<u8>()
+      .map(|_| ())
+      .map_err(|_| format!("{} is not a number", value))
+  };
 
-  let _ = tokio::join!(task::spawn(server.clone()), solve(server));
+  clap::App::new("cryptopals-rust")
+    .about("Runs the cryptopals challenges")
+    .arg(
+      Arg::with_name("challenge")
+        .help("Challenges to run, slow ones included. Runs everything when no challenge or set is given")
+        .multiple(true)
+        .validator(is_number),
+    )
+    .arg(
+      Arg::with_name("set")
+        .help("Runs every challenge in a set")
+        .short("s")
+        .long("set")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .validator(is_number),
+    )
+    .arg(
+      Arg::with_name("slow")
+        .help("Runs slow challenges when running a whole set or everything, instead of skipping them")
+        .long("slow"),
+    )
+    .arg(
+      Arg::with_name("list")
+        .help("Lists the challenges instead of running them")
+        .short("l")
+        .long("list"),
+    )
+    .arg(
+      Arg::with_name("json")
+        .help("Prints JSON instead of plain text")
+        .long("json"),
+    )
 }
 
-lazy_static! {
-  static ref VANILLA: String = fs::read_to_string("data/play-that-funky-music.txt").unwrap();
-  static ref ICE_ICE_BABY: String = fs::read_to_string("data/ice-ice-baby.txt").unwrap();
-  static ref FURY: String = fs::read_to_string("data/fury.txt").unwrap();
+#[actix_web::main]
+pub async fn main() {
+  let matches = cli().get_matches();
+  let numbers = |name| -> Vec<u8> {
+    matches
+      .values_of(name)
+      .map(|values| values.map(|v| v.parse().unwrap()).collect())
+      .unwrap_or_default()
+  };
+  let (picked, sets) = (numbers("challenge"), numbers("set"));
+  let everything = picked.is_empty() && sets.is_empty();
+  let include_slow = matches.is_present("slow");
+  let json = matches.is_present("json");
+
+  let selected: Vec<_> = challenge::all()
+    .into_iter()
+    .filter(|c| everything || picked.contains(&c.number()) || sets.contains(&c.set()))
+    .collect();
+
+  if matches.is_present("list") {
+    print_listing(&selected, json);
+    return;
+  }
+
+  // Slow challenges only run when picked by number or with --slow
+  let skip = |c: &dyn Challenge| c.slow() && !include_slow && !picked.contains(&c.number());
+  let reports = run(selected, skip).await;
+
+  print_reports(&reports, json);
+  if reports.iter().any(|r| r.status == Status::Failed) {
+    process::exit(1);
+  }
 }
@@ -0,0 +1,162 @@
+//! Padding oracle attacks on a `padding_oracle_server`, reading whether
+//! the padding was valid from whichever way the server leaks it.
+
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::crack::{self, PaddingOracle};
+use cryptopals_rust::padding_oracle_server;
+use std::io;
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tokio::task;
+use tokio::time::Duration;
+
+/// Which side channel the attack reads padding validity from.
+#[derive(Clone, Copy, Debug)]
+pub enum Leak {
+  Status,
+  Message,
+  /// Responses slower than this had valid padding.
+  Timing(Duration),
+}
+
+impl Leak {
+  fn path(&self) -> &'static str {
+    match self {
+      Leak::Status => "status",
+      Leak::Message => "message",
+      Leak::Timing(_) => "timing",
+    }
+  }
+}
+
+/// A padding oracle at the other end of an HTTP connection. Only blocks on
+/// requests, so it has to be used off the async runtime, for instance from
+/// `task::spawn_blocking`.
+pub struct RemoteOracle {
+  client: reqwest::Client,
+  url: String,
+  leak: Leak,
+  concurrency: usize,
+  runtime: Handle,
+}
+
+impl RemoteOracle {
+  pub fn new(server: &str, leak: Leak, concurrency: usize, runtime: Handle) -> Self {
+    RemoteOracle {
+      client: reqwest::Client::new(),
+      url: format!("{}/padding-oracle/{}", server, leak.path()),
+      leak,
+      concurrency,
+      runtime,
+    }
+  }
+}
+
+async fn leaks_valid_padding(leak: Leak, request: reqwest::RequestBuilder) -> bool {
+  let start = Instant::now();
+  let response = request.send().await.expect("Padding oracle request failed");
+  match leak {
+    Leak::Status => response.status().as_u16() != 500,
+    Leak::Message => response.text().await.unwrap_or_default() != "Invalid padding",
+    Leak::Timing(threshold) => start.elapsed() > threshold,
+  }
+}
+
+impl PaddingOracle for RemoteOracle {
+  fn block_size(&self) -> usize {
+    16
+  }
+
+  fn has_valid_padding(&self, iv: &[u8], ct: &[u8]) -> bool {
+    self.has_valid_paddings(&[iv.to_vec()], ct)[0]
+  }
+
+  fn concurrency(&self) -> usize {
+    self.concurrency
+  }
+
+  fn has_valid_paddings(&self, ivs: &[Vec<u8>], ct: &[u8]) -> Vec<bool> {
+    self.runtime.block_on(async {
+      let requests: Vec<_> = ivs
+        .iter()
+        .map(|iv| {
+          let token = [&iv[..], ct].concat().to_hex();
+          let request = self.client.get(format!("{}/{}", self.url, token));
+          task::spawn(leaks_valid_padding(self.leak, request))
+        })
+        .collect();
+
+      let mut valid = Vec::new();
+      for request in requests {
+        valid.push(request.await.expect("Padding oracle request panicked"));
+      }
+      valid
+    })
+  }
+}
+
+/// Fetches a token from the server and decrypts it through the chosen leak,
+/// with up to `concurrency` requests in flight at once.
+pub async fn attack(server: &str, leak: Leak, concurrency: usize) -> Option<Vec<u8>> {
+  let token = reqwest::get(format!("{}/padding-oracle/token", server))
+    .await
+    .ok()?
+    .text()
+    .await
+    .ok()?
+    .from_hex()
+    .ok()?;
+  if token.len() < 16 {
+    return None;
+  }
+
+  let oracle = RemoteOracle::new(server, leak, concurrency, Handle::current());
+  task::spawn_blocking(move || {
+    let (iv, ct) = token.split_at(16);
+    crack::padding_oracle_decrypt(&oracle, iv, ct)
+  })
+  .await
+  .ok()?
+}
+
+/// Starts a server on a free port and runs the attack against it.
+pub async fn attack_server(leak: Leak, concurrency: usize) -> io::Result<Option<Vec<u8>>> {
+  let (server, addr) = padding_oracle_server::start("127.0.0.1:0")?;
+  let pt = attack(&format!("http://{}", addr), leak, concurrency).await;
+  server.stop(false).await;
+  Ok(pt)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cryptopals_rust::padding_oracle_server::SESSION;
+
+  #[tokio::test]
+  async fn remote_oracle_decrypts_and_encrypts() {
+    let (server, addr) = padding_oracle_server::start("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", addr);
+    for &leak in &[Leak::Status, Leak::Message] {
+      assert_eq!(
+        attack(&url, leak, 8).await,
+        Some(SESSION.to_vec()),
+        "{:?}",
+        leak
+      );
+    }
+
+    // Forging a token takes the oracle one request at a time
+    let oracle = RemoteOracle::new(&url, Leak::Status, 1, Handle::current());
+    let (iv, ct) =
+      task::spawn_blocking(move || crack::padding_oracle_encrypt(&oracle, b"user=ice"))
+        .await
+        .unwrap()
+        .unwrap();
+    let token = [iv, ct].concat().to_hex();
+    let response = reqwest::get(format!("{}/padding-oracle/status/{}", url, token))
+      .await
+      .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    server.stop(true).await;
+  }
+}
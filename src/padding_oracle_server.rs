@@ -0,0 +1,163 @@
+//! A service that hands out CBC encrypted session tokens and checks them
+//! under `/padding-oracle`, leaking whether the padding was valid in one of
+//! three ways:
+//!
+//! - `/padding-oracle/status/{token}` answers 500 for bad padding and 403
+//!   for a token that decrypts to something that isn't a session.
+//! - `/padding-oracle/message/{token}` always answers 403, but says
+//!   "Invalid padding" or "Invalid session".
+//! - `/padding-oracle/timing/{token}` always answers 403 "Invalid session",
+//!   but only looks the session up, slowly, once the padding checks out.
+//!
+//! `/padding-oracle/token` hands out a fresh token to attack.
+
+use crate::bytes::*;
+use crate::crypt::*;
+use crate::error::Error;
+use actix_web::dev::Server;
+use actix_web::rt::System;
+use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use tokio::time::{sleep, Duration};
+
+/// The session every token decrypts to.
+pub const SESSION: &[u8] = b"user=vanilla-ice;role=rapper;session=ice-ice-baby";
+
+/// How long the timing endpoint spends looking a session up.
+pub const LOOKUP_DELAY: Duration = Duration::from_millis(20);
+
+lazy_static! {
+  static ref KEY: [u8; 16] = random_16();
+}
+
+fn decrypt_token(token: &str) -> Result<Vec<u8>, Error> {
+  let token = token.from_hex()?;
+  if token.len() < 16 {
+    return Err(Error::BadLength);
+  }
+  decrypt_cbc(&KEY[..], &token[..16], &token[16..])
+}
+
+fn is_session(pt: &[u8]) -> bool {
+  pt.starts_with(b"user=")
+}
+
+#[get("/padding-oracle/token")]
+pub async fn new_token() -> impl Responder {
+  let iv = random_16();
+  let ct = encrypt_cbc(&KEY[..], &iv, SESSION).unwrap();
+  HttpResponse::Ok().body([&iv[..], &ct].concat().to_hex())
+}
+
+#[get("/padding-oracle/status/{token}")]
+pub async fn status(token: web::Path<String>) -> impl Responder {
+  match decrypt_token(&token) {
+    Ok(pt) if is_session(&pt) => HttpResponse::Ok().finish(),
+    Err(Error::BadPadding) => HttpResponse::InternalServerError().finish(),
+    _ => HttpResponse::Forbidden().finish(),
+  }
+}
+
+#[get("/padding-oracle/message/{token}")]
+pub async fn message(token: web::Path<String>) -> impl Responder {
+  match decrypt_token(&token) {
+    Ok(pt) if is_session(&pt) => HttpResponse::Ok().finish(),
+    Err(Error::BadPadding) => HttpResponse::Forbidden().body("Invalid padding"),
+    _ => HttpResponse::Forbidden().body("Invalid session"),
+  }
+}
+
+#[get("/padding-oracle/timing/{token}")]
+pub async fn timing(token: web::Path<String>) -> impl Responder {
+  match decrypt_token(&token) {
+    Ok(pt) => {
+      sleep(LOOKUP_DELAY).await;
+      if is_session(&pt) {
+        return HttpResponse::Ok().finish();
+      }
+    }
+    Err(_) => (),
+  }
+  HttpResponse::Forbidden().body("Invalid session")
+}
+
+/// Starts serving on `addr` from a thread of its own, so it can be started
+/// from anywhere. Binding to port 0 picks a free port, which is why the
+/// address actually listened on is returned with the handle to stop it.
+pub fn start<A>(addr: A) -> io::Result<(Server, SocketAddr)>
+where
+  A: ToSocketAddrs + Send + 'static,
+{
+  let (started, starting) = mpsc::channel();
+  thread::spawn(move || {
+    let system = System::new();
+    let server = HttpServer::new(|| {
+      App::new()
+        .service(new_token)
+        .service(status)
+        .service(message)
+        .service(timing)
+    })
+    .disable_signals()
+    .bind(addr);
+    match server {
+      Ok(server) => {
+        let addr = server.addrs()[0];
+        system.block_on(async {
+          let server = server.run();
+          started.send(Ok((server.clone(), addr))).unwrap();
+          server.await.ok();
+        });
+      }
+      Err(err) => started.send(Err(err)).unwrap(),
+    }
+  });
+  starting.recv().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn get(addr: SocketAddr, path: &str) -> (u16, String) {
+    let response = reqwest::get(format!("http://{}/padding-oracle/{}", addr, path))
+      .await
+      .unwrap();
+    let code = response.status().as_u16();
+    (code, response.text().await.unwrap())
+  }
+
+  #[test]
+  fn leaks_padding_three_ways() {
+    let (server, addr) = start("127.0.0.1:0").unwrap();
+    System::new().block_on(async {
+      let token = get(addr, "token").await.1.from_hex().unwrap();
+      assert_eq!(decrypt_token(&token.to_hex()).unwrap(), SESSION);
+
+      // Flipping a bit of the IV flips the same bit of the session, and
+      // flipping one in the last byte before the final block flips one in
+      // the padding
+      let mut bad_session = token.clone();
+      bad_session[0] ^= 1;
+      let mut bad_padding = token.clone();
+      bad_padding[token.len() - 17] ^= 1;
+      let (good, session, padding) = (token.to_hex(), bad_session.to_hex(), bad_padding.to_hex());
+
+      assert_eq!(get(addr, &format!("status/{}", good)).await.0, 200);
+      assert_eq!(get(addr, &format!("status/{}", session)).await.0, 403);
+      assert_eq!(get(addr, &format!("status/{}", padding)).await.0, 500);
+
+      let answer = get(addr, &format!("message/{}", session)).await;
+      assert_eq!(answer, (403, "Invalid session".to_string()));
+      let answer = get(addr, &format!("message/{}", padding)).await;
+      assert_eq!(answer, (403, "Invalid padding".to_string()));
+
+      let answer = get(addr, &format!("timing/{}", padding)).await;
+      assert_eq!(answer, (403, "Invalid session".to_string()));
+      server.stop(true).await;
+    });
+  }
+}
@@ -1,93 +1,663 @@
-const W: u8 = 32;
-const N: usize = 624;
-const M: usize = 397;
-const R: u8 = 31;
-const A: u32 = 0x9908B0DF;
-const U: u8 = 11;
-const S: u8 = 7;
-const B: u32 = 0x9D2C5680;
-const T: u8 = 15;
-const C: u32 = 0xEFC60000;
-const L: u8 = 18;
-const F: u32 = 1812433253;
-const LOWER_MASK: u32 = (1u32 << R) - 1;
-const UPPER_MASK: u32 = !LOWER_MASK;
-
-#[derive(Debug)]
-pub struct Rand {
+//! The Mersenne Twister, with its state laid bare: MT19937 and its 64-bit
+//! sibling MT19937-64, from the same code with different constants. The
+//! submodules have the other generators tokens are often made with, and how
+//! to clone each from its outputs.
+
+pub mod glibc;
+pub mod java;
+pub mod pcg;
+pub mod xorshift;
+
+use crate::gf2::{self, Row, System};
+use ::rand::{RngCore, SeedableRng};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+/// What the state of a twister is made of.
+pub trait Word:
+  Copy
+  + Debug
+  + Eq
+  + BitAnd<Output = Self>
+  + BitOr<Output = Self>
+  + BitXor<Output = Self>
+  + Not<Output = Self>
+  + Shl<u32, Output = Self>
+  + Shr<u32, Output = Self>
+{
+  const BITS: u32;
+  const ZERO: Self;
+  const ONE: Self;
+
+  fn wrapping_add(self, other: Self) -> Self;
+  fn wrapping_sub(self, other: Self) -> Self;
+  fn wrapping_mul(self, other: Self) -> Self;
+  fn from_index(i: usize) -> Self;
+}
+
+macro_rules! word {
+  ($t:ty) => {
+    impl Word for $t {
+      const BITS: u32 = <$t>::BITS;
+      const ZERO: Self = 0;
+      const ONE: Self = 1;
+
+      fn wrapping_add(self, other: Self) -> Self {
+        <$t>::wrapping_add(self, other)
+      }
+
+      fn wrapping_sub(self, other: Self) -> Self {
+        <$t>::wrapping_sub(self, other)
+      }
+
+      fn wrapping_mul(self, other: Self) -> Self {
+        <$t>::wrapping_mul(self, other)
+      }
+
+      fn from_index(i: usize) -> Self {
+        i as $t
+      }
+    }
+  };
+}
+
+word!(u32);
+word!(u64);
+
+/// The constants that make a Mersenne Twister, named as on
+/// [Wikipedia](https://en.wikipedia.org/wiki/Mersenne_Twister), and the
+/// tempering they make.
+pub trait Params {
+  type Word: Word;
+  const N: usize;
+  const M: usize;
+  const R: u32;
+  const A: Self::Word;
+  const U: u32;
+  const D: Self::Word;
+  const S: u32;
+  const B: Self::Word;
+  const T: u32;
+  const C: Self::Word;
+  const L: u32;
+  const F: Self::Word;
+  /// What `init_by_array` seeds with before mixing in the key, and the
+  /// multipliers it mixes with.
+  const ARRAY_SEED: Self::Word;
+  const ARRAY_A: Self::Word;
+  const ARRAY_B: Self::Word;
+
+  /// The word of seeded state at `i` from the one before it.
+  fn seed_step(previous: Self::Word, i: usize) -> Self::Word {
+    Self::F
+      .wrapping_mul(previous ^ (previous >> (Self::Word::BITS - 2)))
+      .wrapping_add(Self::Word::from_index(i))
+  }
+
+  /// What twisting makes of `mt[i]`, from it, `mt[i + 1]` and `mt[i + M]`.
+  fn twisted(word: Self::Word, next: Self::Word, far: Self::Word) -> Self::Word {
+    let lower_mask = !(!Self::Word::ZERO << Self::R);
+    let x = (word & !lower_mask) | (next & lower_mask);
+    let x = if x & Self::Word::ONE == Self::Word::ZERO {
+      x >> 1
+    } else {
+      (x >> 1) ^ Self::A
+    };
+    far ^ x
+  }
+
+  fn temper(x: Self::Word) -> Self::Word {
+    let mut y = x;
+    y = y ^ ((y >> Self::U) & Self::D);
+    y = y ^ ((y << Self::S) & Self::B);
+    y = y ^ ((y << Self::T) & Self::C);
+    y ^ (y >> Self::L)
+  }
+
+  fn untemper(y: Self::Word) -> Self::Word {
+    let ones = !Self::Word::ZERO;
+    let mut x = y;
+    reverse_right(&mut x, Self::L, ones);
+    reverse_left(&mut x, Self::T, Self::C);
+    reverse_left(&mut x, Self::S, Self::B);
+    reverse_right(&mut x, Self::U, Self::D);
+    x
+  }
+}
+
+/// The original, 32 bits at a time.
+#[derive(Clone, Copy, Debug)]
+pub struct Mt19937;
+
+impl Params for Mt19937 {
+  type Word = u32;
+  const N: usize = 624;
+  const M: usize = 397;
+  const R: u32 = 31;
+  const A: u32 = 0x9908B0DF;
+  const U: u32 = 11;
+  const D: u32 = 0xFFFFFFFF;
+  const S: u32 = 7;
+  const B: u32 = 0x9D2C5680;
+  const T: u32 = 15;
+  const C: u32 = 0xEFC60000;
+  const L: u32 = 18;
+  const F: u32 = 1812433253;
+  const ARRAY_SEED: u32 = 19650218;
+  const ARRAY_A: u32 = 1664525;
+  const ARRAY_B: u32 = 1566083941;
+}
+
+/// The 64-bit version, behind `std::mt19937_64` in C++ among others.
+#[derive(Clone, Copy, Debug)]
+pub struct Mt19937_64;
+
+impl Params for Mt19937_64 {
+  type Word = u64;
+  const N: usize = 312;
+  const M: usize = 156;
+  const R: u32 = 31;
+  const A: u64 = 0xB5026F5AA96619E9;
+  const U: u32 = 29;
+  const D: u64 = 0x5555555555555555;
+  const S: u32 = 17;
+  const B: u64 = 0x71D67FFFEDA60000;
+  const T: u32 = 37;
+  const C: u64 = 0xFFF7EEE000000000;
+  const L: u32 = 43;
+  const F: u64 = 6364136223846793005;
+  const ARRAY_SEED: u64 = 19650218;
+  const ARRAY_A: u64 = 3935559000370003845;
+  const ARRAY_B: u64 = 2862933555777941757;
+}
+
+#[derive(Clone, Debug)]
+pub struct Twister<P: Params> {
   pub index: usize,
-  pub mt: [u32; N],
+  pub mt: Vec<P::Word>,
+  params: PhantomData<P>,
 }
 
-impl Iterator for Rand {
-  type Item = u32;
+pub type Rand = Twister<Mt19937>;
+pub type Rand64 = Twister<Mt19937_64>;
 
-  fn next(&mut self) -> Option<u32> {
-    if self.index >= N {
-      twist(self);
+impl<P: Params> Iterator for Twister<P> {
+  type Item = P::Word;
+
+  fn next(&mut self) -> Option<P::Word> {
+    if self.index >= P::N {
+      self.twist();
     }
 
     let y = self.mt[self.index];
     self.index += 1;
 
-    Some(temper(y))
+    Some(P::temper(y))
   }
 }
 
 pub fn random_from_seed(seed: u32) -> Rand {
-  let mut rand = Rand {
-    index: N,
-    mt: [0u32; N],
-  };
-  rand.seed(seed);
-  rand
+  Rand::new(seed)
 }
 
-impl Rand {
-  pub fn seed(&mut self, seed: u32) {
+/// Draws the way Python's `random` does: 64 bits are two outputs, the first
+/// at the bottom, and bytes come four to an output, least significant first,
+/// with the top bytes of one more output for any left over.
+impl RngCore for Rand {
+  fn next_u32(&mut self) -> u32 {
+    self.next().unwrap()
+  }
+
+  fn next_u64(&mut self) -> u64 {
+    let low = self.next_u32() as u64;
+    (self.next_u32() as u64) << 32 | low
+  }
+
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    let mut chunks = dest.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+      chunk.copy_from_slice(&self.next_u32().to_le_bytes());
+    }
+    let rest = chunks.into_remainder();
+    if !rest.is_empty() {
+      let last = self.next_u32() >> (32 - 8 * rest.len());
+      rest.copy_from_slice(&last.to_le_bytes()[..rest.len()]);
+    }
+  }
+
+  fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ::rand::Error> {
+    self.fill_bytes(dest);
+    Ok(())
+  }
+}
+
+/// Seeds with the reference `init_genrand` from a little-endian `u32`, or
+/// with `init_by_array` from the 32-bit words of a `u64`, as Python does.
+impl SeedableRng for Rand {
+  type Seed = [u8; 4];
+
+  fn from_seed(seed: [u8; 4]) -> Self {
+    Twister::new(u32::from_le_bytes(seed))
+  }
+
+  fn seed_from_u64(state: u64) -> Self {
+    let key = if state >> 32 == 0 {
+      vec![state as u32]
+    } else {
+      vec![state as u32, (state >> 32) as u32]
+    };
+    let mut rand = Twister::new(0);
+    rand.init_by_array(&key);
+    rand
+  }
+}
+
+impl<P: Params> Twister<P> {
+  /// A twister that will next give `mt[index]`, tempered. Past the end of
+  /// `mt`, that's after twisting.
+  pub fn from_state(index: usize, mt: Vec<P::Word>) -> Self {
+    assert_eq!(mt.len(), P::N, "the state is N words");
+    Twister {
+      index,
+      mt,
+      params: PhantomData,
+    }
+  }
+
+  pub fn new(seed: P::Word) -> Self {
+    let mut twister = Twister::from_state(P::N, vec![P::Word::ZERO; P::N]);
+    twister.seed(seed);
+    twister
+  }
+
+  pub fn seed(&mut self, seed: P::Word) {
     self.mt[0] = seed;
-    for i in 1usize..N {
-      self.mt[i] = F.wrapping_mul(self.mt[i - 1] ^ (self.mt[i - 1] >> (W - 2))) + i as u32;
+    for i in 1..P::N {
+      self.mt[i] = P::seed_step(self.mt[i - 1], i);
+    }
+    self.index = P::N;
+  }
+
+  /// The output at `index` after seeding with `seed`, counting from 0. The
+  /// first N - M outputs only need the state up to M words past them, which
+  /// makes trying seeds for them several times faster.
+  pub fn output_from_seed(seed: P::Word, index: usize) -> P::Word {
+    if index >= P::N - P::M {
+      return Twister::<P>::new(seed).nth(index).unwrap();
+    }
+    let mut mt = Vec::with_capacity(index + P::M + 1);
+    mt.push(seed);
+    for i in 1..=index + P::M {
+      mt.push(P::seed_step(mt[i - 1], i));
+    }
+    P::temper(P::twisted(mt[index], mt[index + 1], mt[index + P::M]))
+  }
+
+  /// Seeds from a key of any length but 0, as the reference `init_by_array`
+  /// does. It's how Python seeds its `random` from an integer, among others.
+  pub fn init_by_array(&mut self, key: &[P::Word]) {
+    assert!(!key.is_empty(), "init_by_array needs a key");
+    let w = P::Word::BITS;
+    let mix = |mt: &[P::Word], i: usize, multiplier: P::Word| {
+      mt[i] ^ (mt[i - 1] ^ (mt[i - 1] >> (w - 2))).wrapping_mul(multiplier)
+    };
+    self.seed(P::ARRAY_SEED);
+    let (mut i, mut j) = (1, 0);
+    for _ in 0..P::N.max(key.len()) {
+      self.mt[i] = mix(&self.mt, i, P::ARRAY_A)
+        .wrapping_add(key[j])
+        .wrapping_add(P::Word::from_index(j));
+      i += 1;
+      j += 1;
+      if i >= P::N {
+        self.mt[0] = self.mt[P::N - 1];
+        i = 1;
+      }
+      if j >= key.len() {
+        j = 0;
+      }
+    }
+    for _ in 0..P::N - 1 {
+      self.mt[i] = mix(&self.mt, i, P::ARRAY_B).wrapping_sub(P::Word::from_index(i));
+      i += 1;
+      if i >= P::N {
+        self.mt[0] = self.mt[P::N - 1];
+        i = 1;
+      }
     }
+    // Top bit set, so the state isn't all zeros
+    self.mt[0] = P::Word::ONE << (w - 1);
+    self.index = P::N;
+  }
+
+  /// Clones a generator from N or more consecutive outputs, starting at any
+  /// point in its stream. The clone carries on where the outputs leave off,
+  /// and any past the first N have to agree with it.
+  pub fn clone_from_outputs(outputs: &[P::Word]) -> Option<Self> {
+    if outputs.len() < P::N {
+      return None;
+    }
+    let mt = outputs[..P::N]
+      .iter()
+      .map(|&output| P::untemper(output))
+      .collect();
+    let mut clone = Twister::from_state(P::N, mt);
+    if outputs[P::N..]
+      .iter()
+      .all(|&output| clone.next() == Some(output))
+    {
+      Some(clone)
+    } else {
+      None
+    }
+  }
+
+  // Generate next N values
+  fn twist(&mut self) {
+    for i in 0..P::N {
+      self.mt[i] = P::twisted(
+        self.mt[i],
+        self.mt[(i + 1) % P::N],
+        self.mt[(i + P::M) % P::N],
+      );
+    }
+    self.index = 0;
   }
 }
 
-pub fn temper(x: u32) -> u32 {
-  let mut y = x;
-  y ^= y >> U;
-  y ^= (y << S) & B;
-  y ^= (y << T) & C;
-  y ^= y >> L;
-  y
+/// Some of the bits of one output: those of `value` under `mask`, with
+/// `index` counting outputs from the first one observed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Observation {
+  pub index: usize,
+  pub value: u32,
+  pub mask: u32,
 }
 
-pub fn untemper(y: u32) -> u32 {
-  let mut x = y;
-  reverse_right(&mut x, L);
-  reverse_left(&mut x, T, C);
-  reverse_left(&mut x, S, B);
-  reverse_right(&mut x, U);
-  x
+impl Observation {
+  pub fn full(index: usize, value: u32) -> Self {
+    Observation {
+      index,
+      value,
+      mask: !0,
+    }
+  }
+
+  /// Just the top `bits` bits of an output, shifted down, as in a number
+  /// below `1 << bits` taken from the high end.
+  pub fn top(index: usize, value: u32, bits: u32) -> Self {
+    let mask = (!0u64 << (32 - bits)) as u32;
+    Observation {
+      index,
+      value: ((value as u64) << (32 - bits)) as u32,
+      mask,
+    }
+  }
+
+  fn matches(&self, output: u32) -> bool {
+    (output ^ self.value) & self.mask == 0
+  }
 }
 
-fn reverse_left(x: &mut u32, n: u8, mask: u32) {
-  for i in 0..32 {
-    *x ^= (*x << n) & (1 << i) & mask;
+// Recovery works on MT19937 alone
+const N: usize = Mt19937::N;
+const W: usize = 32;
+
+/// Bits of state the first 31 bits of `mt[0]` don't feed into any output
+/// after the first, so they need not be known.
+const IRRELEVANT: usize = 31;
+
+impl Rand {
+  /// Clones a generator from whatever bits of its outputs were seen, in any
+  /// order and with gaps. Every output bit is a linear function of the state
+  /// bits, so each one seen is an equation over GF(2) and the state is what
+  /// solves them. That takes at least as many bits as there are of state.
+  /// The clone carries on after the last output observed.
+  pub fn recover(observations: &[Observation]) -> Option<Rand> {
+    let mut observations = observations.to_vec();
+    observations.sort_by_key(|observation| observation.index);
+    let last = observations.last()?.index;
+
+    // The state when the first output was taken, one unknown per bit, and
+    // the same state twisted along as the observations call for
+    let unknowns = N * W;
+    let mut state: Vec<[Row; 32]> = (0..N)
+      .map(|i| {
+        let mut word: [Row; 32] = Default::default();
+        for (bit, row) in word.iter_mut().enumerate() {
+          *row = gf2::row(unknowns);
+          gf2::flip(row, i * W + bit);
+        }
+        word
+      })
+      .collect();
+    let mut twists = 0;
+
+    let tempered = tempering_matrix();
+    let mut system = System::new(unknowns);
+    for observation in &observations {
+      while twists < observation.index / N {
+        twist_symbolic(&mut state);
+        twists += 1;
+      }
+      let word = &state[observation.index % N];
+      for bit in (0..32).filter(|bit| observation.mask >> bit & 1 == 1) {
+        let mut row = gf2::row(unknowns);
+        for from in (0..32).filter(|from| tempered[bit] >> from & 1 == 1) {
+          gf2::add(&mut row, &word[from]);
+        }
+        system.add(row, observation.value >> bit & 1 == 1);
+      }
+    }
+
+    if system.rank() < unknowns - IRRELEVANT {
+      return None;
+    }
+    let x = system.solve()?;
+    let mut mt = vec![0u32; N];
+    for (i, state) in mt.iter_mut().enumerate() {
+      for bit in 0..32 {
+        if gf2::get(&x, i * W + bit) {
+          *state |= 1 << bit;
+        }
+      }
+    }
+
+    let mut clone = Rand::from_state(0, mt);
+    let mut next = 0;
+    for observation in &observations {
+      while next < observation.index {
+        clone.next();
+        next += 1;
+      }
+      if !observation.matches(clone.next().unwrap()) {
+        return None;
+      }
+      next += 1;
+    }
+    while next <= last {
+      clone.next();
+      next += 1;
+    }
+    Some(clone)
   }
 }
 
-fn reverse_right(x: &mut u32, n: u8) {
-  for i in (0..32).rev() {
-    *x ^= (*x >> n) & (1 << i);
+/// Which bits of the untempered output each tempered bit is the XOR of.
+fn tempering_matrix() -> [u32; 32] {
+  let mut matrix = [0u32; 32];
+  for from in 0..32 {
+    let column = Mt19937::temper(1 << from);
+    for (bit, row) in matrix.iter_mut().enumerate() {
+      *row |= (column >> bit & 1) << from;
+    }
   }
+  matrix
 }
 
-// Generate next N values
-fn twist(rand: &mut Rand) {
+/// `twist` on a state whose bits are rows of coefficients.
+fn twist_symbolic(state: &mut [[Row; 32]]) {
   for i in 0..N {
-    let x = (rand.mt[i] & UPPER_MASK) | rand.mt[(i + 1) % N] & LOWER_MASK;
-    let x = if x & 0x1 == 0 { x >> 1 } else { (x >> 1) ^ A };
-    rand.mt[i] = rand.mt[(i + M) % N] ^ x;
+    // x takes its top bit from mt[i] and the rest from mt[i + 1]
+    let x: Vec<&Row> = (0..32)
+      .map(|bit| {
+        if bit == 31 {
+          &state[i][bit]
+        } else {
+          &state[(i + 1) % N][bit]
+        }
+      })
+      .collect();
+    let mut word: [Row; 32] = Default::default();
+    for (bit, row) in word.iter_mut().enumerate() {
+      *row = state[(i + Mt19937::M) % N][bit].clone();
+      if bit < 31 {
+        gf2::add(row, x[bit + 1]);
+      }
+      if Mt19937::A >> bit & 1 == 1 {
+        gf2::add(row, x[0]);
+      }
+    }
+    state[i] = word;
+  }
+}
+
+fn reverse_left<T: Word>(x: &mut T, n: u32, mask: T) {
+  for i in 0..T::BITS {
+    *x = *x ^ ((*x << n) & (T::ONE << i) & mask);
+  }
+}
+
+fn reverse_right<T: Word>(x: &mut T, n: u32, mask: T) {
+  for i in (0..T::BITS).rev() {
+    *x = *x ^ ((*x >> n) & (T::ONE << i) & mask);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bytes::*;
+  use ::rand::seq::SliceRandom;
+  use ::rand::Rng;
+
+  #[test]
+  fn untemper_undoes_temper() {
+    for &x in &[0, 1, 0xdeadbeef, !0] {
+      assert_eq!(Mt19937::untemper(Mt19937::temper(x)), x);
+    }
+    for &x in &[0, 1, 0xdeadbeefcafebabe, !0] {
+      assert_eq!(Mt19937_64::untemper(Mt19937_64::temper(x)), x);
+    }
+  }
+
+  // The outputs from the default seed that C++ requires of std::mt19937 and
+  // std::mt19937_64: the first, and the 10000th
+
+  #[test]
+  fn mt19937_reference_outputs() {
+    let mut generator = Rand::new(5489);
+    assert_eq!(generator.next(), Some(3499211612));
+    assert_eq!(generator.nth(9998), Some(4123659995));
+  }
+
+  #[test]
+  fn mt19937_64_reference_outputs() {
+    let mut generator = Rand64::new(5489);
+    assert_eq!(generator.next(), Some(14514284786278117030));
+    assert_eq!(generator.nth(9998), Some(9981545732273789042));
+  }
+
+  // From the reference implementations' own test programs
+
+  #[test]
+  fn init_by_array_reference_outputs() {
+    let mut generator = Rand::new(0);
+    generator.init_by_array(&[0x123, 0x234, 0x345, 0x456]);
+    assert_eq!(generator.next(), Some(1067595299));
+
+    let mut generator = Rand64::new(0);
+    generator.init_by_array(&[0x12345, 0x23456, 0x34567, 0x45678]);
+    assert_eq!(generator.next(), Some(7266447313870364031));
+  }
+
+  // What Python's random gives after random.seed(n)
+
+  #[test]
+  fn draws_like_python() {
+    let mut rng = Rand::seed_from_u64(0);
+    let draws: Vec<u32> = (0..3).map(|_| rng.gen()).collect();
+    assert_eq!(draws, [3626764237, 1654615998, 3255389356]);
+
+    let mut rng = Rand::seed_from_u64((1 << 40) + 5);
+    assert_eq!(rng.next_u32(), 2166296868);
+    assert_eq!(rng.next_u64(), 4954877310874744636);
+
+    let mut rng = Rand::seed_from_u64(1);
+    let mut bytes = [0; 7];
+    rng.fill_bytes(&mut bytes);
+    assert_eq!(bytes.to_hex(), "f5b1652258b791");
+  }
+
+  #[test]
+  fn drives_the_rand_crate() {
+    let mut rng = Rand::from_seed(5489u32.to_le_bytes());
+    assert_eq!(rng.gen::<u32>(), 3499211612);
+
+    let shuffled = |rng: &mut Rand| {
+      let mut deck: Vec<u32> = (0..52).collect();
+      deck.shuffle(rng);
+      deck
+    };
+    let deck = shuffled(&mut rng);
+    assert_ne!(deck, (0..52).collect::<Vec<_>>());
+    let mut replay = Rand::new(5489);
+    replay.next();
+    assert_eq!(shuffled(&mut replay), deck);
+  }
+
+  #[test]
+  fn clones_mt19937_64() {
+    let mut generator = Rand64::new(42);
+    let outputs: Vec<_> = generator.by_ref().take(Mt19937_64::N).collect();
+    let clone = Rand64::clone_from_outputs(&outputs).unwrap();
+    assert!(clone.take(1000).eq(generator.take(1000)));
+  }
+
+  #[test]
+  fn clones_from_full_outputs() {
+    let mut generator = random_from_seed(5489);
+    generator.nth(1000);
+    let outputs: Vec<_> = generator.by_ref().take(N + 10).collect();
+    let clone = Rand::clone_from_outputs(&outputs).unwrap();
+    assert!(clone.take(1000).eq(generator.take(1000)));
+
+    assert!(Rand::clone_from_outputs(&outputs[..N - 1]).is_none());
+    let mut tampered = outputs;
+    tampered[N + 5] ^= 1;
+    assert!(Rand::clone_from_outputs(&tampered).is_none());
+  }
+
+  #[test]
+  fn recovers_from_top_bits() {
+    let mut generator = random_from_seed(1);
+    let observations: Vec<_> = (0..2600)
+      .map(|i| Observation::top(i, generator.next().unwrap() >> 24, 8))
+      .collect();
+    let clone = Rand::recover(&observations).unwrap();
+    assert!(clone.take(1000).eq(generator.take(1000)));
+  }
+
+  #[test]
+  fn recovers_across_gaps() {
+    let mut generator = random_from_seed(2);
+    let observations: Vec<_> = (0..=2100)
+      .map(|i| (i, generator.next().unwrap()))
+      .filter(|(i, _)| i % 3 == 0)
+      .map(|(i, output)| Observation::full(i, output))
+      .collect();
+    let clone = Rand::recover(&observations).unwrap();
+    assert!(clone.take(1000).eq(generator.take(1000)));
   }
-  rand.index = 0;
 }
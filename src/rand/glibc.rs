@@ -0,0 +1,130 @@
+//! glibc's `rand()`, an additive feedback generator: each value is the sum
+//! of the ones 3 and 31 before it, and `rand()` drops its lowest bit.
+
+use crate::gf2::{self, System};
+
+const DEGREE: usize = 31;
+const SEPARATION: usize = 3;
+/// Values `srand` makes and throws away before `rand()` hands any out.
+const DISCARD: usize = 310;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlibcRand {
+  /// The last 31 values, oldest at `index`.
+  r: [u32; DEGREE],
+  index: usize,
+}
+
+impl GlibcRand {
+  /// As `srand(seed)`.
+  pub fn new(seed: u32) -> Self {
+    let mut r = [0u32; DEGREE];
+    r[0] = if seed == 0 { 1 } else { seed };
+    for i in 1..DEGREE {
+      // 16807 * r[i - 1] % (2^31 - 1), done as glibc does it in 32 bits
+      let previous = r[i - 1] as i32;
+      let (hi, lo) = (previous / 127773, previous % 127773);
+      let word = 16807 * lo - 2836 * hi;
+      r[i] = if word < 0 { word + 2147483647 } else { word } as u32;
+    }
+    // The oldest value is 3 along, where glibc starts adding to
+    let mut rand = GlibcRand {
+      r,
+      index: SEPARATION,
+    };
+    for _ in 0..DISCARD {
+      rand.step();
+    }
+    rand
+  }
+
+  fn step(&mut self) -> u32 {
+    let value =
+      self.r[self.index].wrapping_add(self.r[(self.index + DEGREE - SEPARATION) % DEGREE]);
+    self.r[self.index] = value;
+    self.index = (self.index + 1) % DEGREE;
+    value
+  }
+
+  /// Clones a generator from a run of its outputs, carrying on after them.
+  ///
+  /// Each output is the sum of those 3 and 31 before it, plus 1 when the
+  /// bits `rand()` dropped from both were set. Those dropped bits follow the
+  /// same recurrence over GF(2), so they're linear in the 31 that came first,
+  /// and every carry seen pins two of them down. A few hundred outputs have
+  /// enough carries to pin them all.
+  pub fn clone_from_outputs(outputs: &[u32]) -> Option<Self> {
+    if outputs.len() <= DEGREE {
+      return None;
+    }
+    // Each dropped bit in terms of the first 31
+    let mut dropped: Vec<gf2::Row> = (0..DEGREE)
+      .map(|i| {
+        let mut row = gf2::row(DEGREE);
+        gf2::flip(&mut row, i);
+        row
+      })
+      .collect();
+    let mut system = System::new(DEGREE);
+    for i in DEGREE..outputs.len() {
+      let mut row = dropped[i - DEGREE].clone();
+      gf2::add(&mut row, &dropped[i - SEPARATION]);
+      let sum = outputs[i - DEGREE].wrapping_add(outputs[i - SEPARATION]) & 0x7fffffff;
+      match outputs[i].wrapping_sub(sum) & 0x7fffffff {
+        0 => (),
+        1 => {
+          system.add(dropped[i - DEGREE].clone(), true);
+          system.add(dropped[i - SEPARATION].clone(), true);
+        }
+        _ => return None,
+      }
+      dropped.push(row);
+    }
+    if system.rank() < DEGREE {
+      return None;
+    }
+
+    let bits = system.solve()?;
+    let mut r = [0u32; DEGREE];
+    for (i, word) in r.iter_mut().enumerate() {
+      *word = outputs[i] << 1 | gf2::get(&bits, i) as u32;
+    }
+    let mut clone = GlibcRand { r, index: 0 };
+    if outputs[DEGREE..]
+      .iter()
+      .all(|&output| clone.next() == Some(output))
+    {
+      Some(clone)
+    } else {
+      None
+    }
+  }
+}
+
+impl Iterator for GlibcRand {
+  type Item = u32;
+
+  fn next(&mut self) -> Option<u32> {
+    Some(self.step() >> 1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_glibc() {
+    let outputs: Vec<_> = GlibcRand::new(1).take(4).collect();
+    assert_eq!(outputs, [1804289383, 846930886, 1681692777, 1714636915]);
+  }
+
+  #[test]
+  fn clones_from_outputs() {
+    let mut generator = GlibcRand::new(rand::random());
+    let outputs: Vec<_> = generator.by_ref().take(400).collect();
+    let clone = GlibcRand::clone_from_outputs(&outputs).unwrap();
+    assert!(clone.take(1000).eq(generator.take(1000)));
+    assert_eq!(GlibcRand::clone_from_outputs(&outputs[..20]), None);
+  }
+}
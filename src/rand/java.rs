@@ -0,0 +1,95 @@
+//! `java.util.Random`, a 48-bit LCG that hands out the top bits of its state.
+
+const MULTIPLIER: u64 = 0x5DEECE66D;
+const ADDEND: u64 = 0xB;
+const MASK: u64 = (1 << 48) - 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JavaRandom {
+  /// The 48 bits of state, as scrambled from the seed.
+  pub state: u64,
+}
+
+impl JavaRandom {
+  /// As `new Random(seed)`.
+  pub fn new(seed: i64) -> Self {
+    JavaRandom {
+      state: (seed as u64 ^ MULTIPLIER) & MASK,
+    }
+  }
+
+  /// The top `bits` of the next state, as `Random.next(bits)`.
+  pub fn next_bits(&mut self, bits: u32) -> i32 {
+    self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(ADDEND) & MASK;
+    (self.state >> (48 - bits)) as i32
+  }
+
+  pub fn next_int(&mut self) -> i32 {
+    self.next_bits(32)
+  }
+
+  /// As `nextInt(bound)`, which throws away draws that would favour small
+  /// numbers.
+  pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+    assert!(bound > 0, "bound must be positive");
+    if bound & -bound == bound {
+      return ((bound as i64 * self.next_bits(31) as i64) >> 31) as i32;
+    }
+    loop {
+      let bits = self.next_bits(31);
+      let value = bits % bound;
+      if bits.wrapping_sub(value).wrapping_add(bound - 1) >= 0 {
+        return value;
+      }
+    }
+  }
+
+  pub fn next_long(&mut self) -> i64 {
+    ((self.next_int() as i64) << 32).wrapping_add(self.next_int() as i64)
+  }
+
+  /// Clones a generator from two `nextInt()`s in a row. The first is all but
+  /// 16 bits of the state, and the second tells which of those 16 it was.
+  /// The clone carries on after the second.
+  pub fn from_ints(first: i32, second: i32) -> Option<Self> {
+    let high = (first as u32 as u64) << 16;
+    (0..1 << 16)
+      .map(|low| JavaRandom { state: high | low })
+      .find(|candidate| candidate.clone().next_int() == second)
+      .map(|mut clone| {
+        clone.next_int();
+        clone
+      })
+  }
+
+  /// Clones a generator from one `nextLong()`, which is two `nextInt()`s.
+  pub fn from_long(long: i64) -> Option<Self> {
+    let second = long as i32;
+    let first = (long.wrapping_sub(second as i64) >> 32) as i32;
+    JavaRandom::from_ints(first, second)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_java() {
+    assert_eq!(JavaRandom::new(42).next_int(), -1170105035);
+    assert_eq!(JavaRandom::new(0).next_int(), -1155484576);
+    assert_eq!(JavaRandom::new(0).next_long(), -4962768465676381896);
+  }
+
+  #[test]
+  fn clones_from_outputs() {
+    let mut random = JavaRandom::new(rand::random());
+    let (first, second) = (random.next_int(), random.next_int());
+    let mut clone = JavaRandom::from_ints(first, second).unwrap();
+    assert_eq!(clone.next_int_bounded(1000), random.next_int_bounded(1000));
+
+    let mut clone = JavaRandom::from_long(random.next_long()).unwrap();
+    assert_eq!(clone, random);
+    assert_eq!(clone.next_int_bounded(64), random.next_int_bounded(64));
+  }
+}
@@ -0,0 +1,186 @@
+//! PCG32, the XSH RR variant: a 64-bit LCG whose state is squeezed into 32
+//! bits of output by an xorshift and a rotation chosen by its top bits.
+//!
+//! Cloning one takes knowing its stream, `inc`: `DEFAULT_INC` unless it was
+//! seeded with a sequence of its own. An unknown stream can't be worked out
+//! from the outputs. Without it only the differences between states are
+//! known, and each state is the first plus a multiple of the first
+//! difference, so the low 27 bits of the first only ever show up in the
+//! outputs as the odd carry.
+
+/// The stream of generators that don't pick one, as in `pcg32_oneseq`.
+pub const DEFAULT_INC: u64 = 1442695040888963407;
+
+const MULTIPLIER: u64 = 6364136223846793005;
+/// The bits of state below those that make it into an output.
+const HIDDEN: u32 = 27;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pcg32 {
+  pub state: u64,
+  /// What the LCG adds, which picks the stream. Always odd.
+  pub inc: u64,
+}
+
+impl Pcg32 {
+  /// As `pcg32_srandom_r(initstate, initseq)`.
+  pub fn new(initstate: u64, initseq: u64) -> Self {
+    let mut pcg = Pcg32 {
+      state: 0,
+      inc: initseq << 1 | 1,
+    };
+    pcg.step();
+    pcg.state = pcg.state.wrapping_add(initstate);
+    pcg.step();
+    pcg
+  }
+
+  fn step(&mut self) -> u64 {
+    let old = self.state;
+    self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.inc);
+    old
+  }
+
+  /// Clones a generator on stream `inc` from 3 or more of its outputs in a
+  /// row, carrying on after them.
+  ///
+  /// Rotating an output back by the right amount gives the top 37 bits of
+  /// the state it came from, so guessing the rotations of the first two
+  /// leaves 27 unknown bits, `low`, with `MULTIPLIER * low` known to within
+  /// 2^27 modulo 2^64. The smallest `low` that fits is found like a GCD.
+  pub fn recover(outputs: &[u32], inc: u64) -> Option<Self> {
+    if outputs.len() < 3 {
+      return None;
+    }
+    for first in 0..32 {
+      let high0 = high_bits(outputs[0], first);
+      for second in 0..32 {
+        let high1 = high_bits(outputs[1], second);
+        // MULTIPLIER * low lands in [distance, distance + 2^27)
+        let distance = high1
+          .wrapping_sub(MULTIPLIER.wrapping_mul(high0))
+          .wrapping_sub(inc);
+        let mut from = 0;
+        while let Some(low) = next_fit(distance, from) {
+          let mut clone = Pcg32 {
+            state: high0 | low,
+            inc,
+          };
+          if outputs.iter().all(|&output| clone.next() == Some(output)) {
+            return Some(clone);
+          }
+          from = low + 1;
+        }
+      }
+    }
+    None
+  }
+}
+
+impl Iterator for Pcg32 {
+  type Item = u32;
+
+  fn next(&mut self) -> Option<u32> {
+    let old = self.step();
+    let xorshifted = ((old >> 18 ^ old) >> HIDDEN) as u32;
+    Some(xorshifted.rotate_right((old >> 59) as u32))
+  }
+}
+
+/// The top 37 bits of the state behind `output`, if it was rotated by
+/// `rotation`. The rotation is the top 5, the xorshift left the next 13
+/// alone, and the rest each come from an output bit and one 18 above.
+fn high_bits(output: u32, rotation: u32) -> u64 {
+  let xorshifted = output.rotate_left(rotation) as u64;
+  let mut state = (rotation as u64) << 59 | (xorshifted >> 19) << 46;
+  for bit in (HIDDEN..46).rev() {
+    let value = (xorshifted >> (bit - HIDDEN) ^ state >> (bit + 18)) & 1;
+    state |= value << bit;
+  }
+  state
+}
+
+/// The smallest `low` from `from` up to 2^27 with `MULTIPLIER * low`
+/// within 2^27 above `distance`, modulo 2^64.
+fn next_fit(distance: u64, from: u64) -> Option<u64> {
+  if from >= 1 << HIDDEN {
+    return None;
+  }
+  // Shifted so the answer is the smallest y, with low = from + y
+  let lower = distance.wrapping_sub(MULTIPLIER.wrapping_mul(from)) as u128;
+  let upper = lower + (1 << HIDDEN) - 1;
+  let modulus = 1u128 << 64;
+  // An interval that wraps past 0 contains it
+  let y = if upper >= modulus {
+    0
+  } else {
+    smallest_multiple(MULTIPLIER as u128, modulus, lower, upper)?
+  };
+  let low = from as u128 + y;
+  if low < 1 << HIDDEN {
+    Some(low as u64)
+  } else {
+    None
+  }
+}
+
+/// The smallest x with `a * x mod m` in `[lower, upper]`, which must be
+/// within `[0, m)`. Each step swaps the problem for one modulo `a`, the way
+/// Euclid's algorithm does.
+fn smallest_multiple(a: u128, m: u128, lower: u128, upper: u128) -> Option<u128> {
+  if lower == 0 {
+    return Some(0);
+  }
+  let a = a % m;
+  if a == 0 {
+    return None;
+  }
+  let x = lower.div_ceil(a);
+  if a * x <= upper {
+    return Some(x);
+  }
+  // No multiple of a fits as is, so some number of wraps past m is needed
+  let wraps = smallest_multiple(a - m % a, a, lower % a, upper % a)?;
+  Some((lower + m * wraps).div_ceil(a))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_reference() {
+    // pcg32-demo's first outputs
+    let outputs: Vec<_> = Pcg32::new(42, 54).take(6).collect();
+    assert_eq!(
+      outputs,
+      vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+    );
+  }
+
+  #[test]
+  fn recovers_state() {
+    for _ in 0..5 {
+      let mut pcg = Pcg32::new(rand::random(), rand::random());
+      let outputs: Vec<_> = pcg.by_ref().take(3).collect();
+      let clone = Pcg32::recover(&outputs, pcg.inc).unwrap();
+      assert_eq!(clone, pcg);
+      assert!(clone.take(100).eq(pcg.take(100)));
+    }
+
+    let mut pcg = Pcg32 {
+      state: rand::random(),
+      inc: DEFAULT_INC,
+    };
+    let outputs: Vec<_> = pcg.by_ref().take(4).collect();
+    assert_eq!(Pcg32::recover(&outputs, DEFAULT_INC), Some(pcg));
+  }
+
+  #[test]
+  fn finds_smallest_multiples() {
+    // 7 * 5 = 35 = 2 mod 11 is the first of 7, 14, 21, 28, 35 in [1, 2]
+    assert_eq!(smallest_multiple(7, 11, 1, 2), Some(5));
+    assert_eq!(smallest_multiple(7, 11, 3, 3), Some(2));
+    assert_eq!(smallest_multiple(4, 8, 1, 3), None);
+  }
+}
@@ -0,0 +1,177 @@
+//! xorshift128+, behind `Math.random()` in V8 and so Chrome and Node. Its
+//! state only ever gets shifted and XORed, so every bit of it is linear over
+//! GF(2) in the bits it started with.
+
+use crate::gf2::{self, Row, System};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Xorshift128Plus {
+  pub state0: u64,
+  pub state1: u64,
+}
+
+impl Xorshift128Plus {
+  pub fn new(state0: u64, state1: u64) -> Self {
+    assert!(state0 | state1 != 0, "an all-zero state stays that way");
+    Xorshift128Plus { state0, state1 }
+  }
+
+  fn step(&mut self) {
+    let mut s1 = self.state0;
+    let s0 = self.state1;
+    self.state0 = s0;
+    s1 ^= s1 << 23;
+    s1 ^= s1 >> 17;
+    s1 ^= s0;
+    s1 ^= s0 >> 26;
+    self.state1 = s1;
+  }
+
+  /// A double in [0, 1) the way V8 makes them: the top 52 bits of `state0`
+  /// after a step, as the mantissa. V8 makes 64 at a time and hands them out
+  /// last first, so outputs from `Math.random()` need reversing in batches
+  /// of 64 to line up with these.
+  pub fn next_f64(&mut self) -> f64 {
+    self.step();
+    f64::from_bits(self.state0 >> 12 | 0x3FF0_0000_0000_0000) - 1.0
+  }
+
+  /// Clones a generator from doubles it made with `next_f64`, carrying on
+  /// after them. Each gives away 52 bits of state, but they overlap enough
+  /// that it takes 4 of them to pin down all 128.
+  pub fn recover_from_f64s(outputs: &[f64]) -> Option<Self> {
+    // The state as rows over the 128 bits it started with, state0 first
+    let mut state0: Vec<Row> = (0..64).map(unit).collect();
+    let mut state1: Vec<Row> = (64..128).map(unit).collect();
+    let mut system = System::new(128);
+    for &output in outputs {
+      if !(0.0..1.0).contains(&output) {
+        return None;
+      }
+      step_symbolic(&mut state0, &mut state1);
+      let mantissa = (output + 1.0).to_bits() & ((1 << 52) - 1);
+      for bit in 0..52 {
+        system.add(state0[bit + 12].clone(), mantissa >> bit & 1 == 1);
+      }
+    }
+    if system.rank() < 128 {
+      return None;
+    }
+
+    let bits = system.solve()?;
+    let word = |offset: usize| {
+      (0..64).fold(0u64, |word, bit| {
+        word | (gf2::get(&bits, offset + bit) as u64) << bit
+      })
+    };
+    let mut clone = Xorshift128Plus {
+      state0: word(0),
+      state1: word(64),
+    };
+    if outputs.iter().all(|&output| clone.next_f64() == output) {
+      Some(clone)
+    } else {
+      None
+    }
+  }
+}
+
+impl Iterator for Xorshift128Plus {
+  type Item = u64;
+
+  /// The usual xorshift128+ output, the sum of the two halves of the state.
+  fn next(&mut self) -> Option<u64> {
+    self.step();
+    Some(self.state0.wrapping_add(self.state1))
+  }
+}
+
+fn unit(bit: usize) -> Row {
+  let mut row = gf2::row(128);
+  gf2::flip(&mut row, bit);
+  row
+}
+
+/// Rows for the bits of `word << n`, or `>> n` when `n` is negative.
+fn shifted(word: &[Row], n: i32) -> Vec<Row> {
+  (0..64)
+    .map(|bit| {
+      let from = bit - n;
+      if (0..64).contains(&from) {
+        word[from as usize].clone()
+      } else {
+        gf2::row(128)
+      }
+    })
+    .collect()
+}
+
+fn xor_into(word: &mut [Row], other: &[Row]) {
+  for (row, other) in word.iter_mut().zip(other) {
+    gf2::add(row, other);
+  }
+}
+
+/// `step` on a state whose bits are rows of coefficients.
+fn step_symbolic(state0: &mut Vec<Row>, state1: &mut Vec<Row>) {
+  let mut s1 = state0.clone();
+  let s0 = state1.clone();
+  let left = shifted(&s1, 23);
+  xor_into(&mut s1, &left);
+  let right = shifted(&s1, -17);
+  xor_into(&mut s1, &right);
+  xor_into(&mut s1, &s0);
+  xor_into(&mut s1, &shifted(&s0, -26));
+  *state0 = s0;
+  *state1 = s1;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SEED: (u64, u64) = (0x0123456789abcdef, 0xfedcba9876543210);
+
+  #[test]
+  fn matches_reference() {
+    // Vigna's xorshift128+ in C
+    let outputs: Vec<_> = Xorshift128Plus::new(SEED.0, SEED.1).take(4).collect();
+    assert_eq!(
+      outputs,
+      [
+        0x4b182deded661907,
+        0x4b182e0a46eba587,
+        0x1738ed21d98fb1ac,
+        0xd54368ca3c0b083e
+      ]
+    );
+  }
+
+  #[test]
+  fn matches_v8() {
+    // V8's XorShift128 then ToDouble
+    let mut generator = Xorshift128Plus::new(SEED.0, SEED.1);
+    let doubles: Vec<_> = (0..4).map(|_| generator.next_f64()).collect();
+    assert_eq!(
+      doubles,
+      [
+        0.9955555555555555,
+        0.29778214298773475,
+        0.9955555621562329,
+        0.09515681421336075
+      ]
+    );
+  }
+
+  #[test]
+  fn clones_from_doubles() {
+    let mut generator = Xorshift128Plus::new(rand::random(), rand::random());
+    let outputs: Vec<_> = (0..4).map(|_| generator.next_f64()).collect();
+    let mut clone = Xorshift128Plus::recover_from_f64s(&outputs).unwrap();
+    assert_eq!(clone, generator);
+    assert_eq!(clone.next_f64(), generator.next_f64());
+    assert!(clone.take(100).eq(generator.take(100)));
+
+    assert_eq!(Xorshift128Plus::recover_from_f64s(&outputs[..3]), None);
+  }
+}
@@ -0,0 +1,336 @@
+//! Timing attacks on comparisons that give up at the first wrong byte.
+
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Guesses per round, so that rounds have a meaningful median.
+const ROUND_SIZE: usize = 16;
+
+/// What checking a guess gave away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timing {
+  Accepted,
+  Rejected(Duration),
+}
+
+/// Checks guesses at a secret, taking a little longer for every leading byte
+/// that is right.
+pub trait TimingOracle {
+  /// Times one check of each guess. Oracles with a slow round trip can make
+  /// the checks concurrently.
+  fn time(&self, guesses: &[Vec<u8>]) -> Vec<Timing>;
+}
+
+/// Recovers a secret from an oracle that leaks through its timing. Each byte
+/// is found by sampling every candidate a few times, then sampling the ones
+/// that still look slow until one is left. Noise only ever makes a check
+/// slower, so the slowest samples of each candidate are dropped.
+///
+/// If a byte is right, every guess for the next byte takes a leak longer than
+/// the wrong guesses for this byte did. When that doesn't hold the attack
+/// backs up and tries the previous byte again without its pick. When it does
+/// hold but no candidate stands out, it just tries the byte again.
+#[derive(Clone, Debug)]
+pub struct TimingAttack {
+  pub length: usize,
+  /// How much longer a check takes for each correct leading byte. Only a
+  /// first guess, the attack measures it once it has a couple of bytes.
+  pub leak: Duration,
+  /// Samples of every candidate before any get rejected.
+  pub min_samples: usize,
+  /// Samples after which the slowest candidate left is picked.
+  pub max_samples: usize,
+  /// Fraction of each candidate's slowest samples ignored as noise.
+  pub outliers: f64,
+  /// Standard errors a candidate has to be off by before it is rejected or
+  /// picked.
+  pub confidence: f64,
+  /// How many times the attack may back up before giving up.
+  pub max_backtracks: usize,
+}
+
+enum Guess {
+  Accepted(Vec<u8>),
+  Byte { byte: u8, baseline: f64 },
+  Nothing { baseline: f64 },
+}
+
+/// Mean, standard deviation and count of the samples left once the slowest
+/// `outliers` fraction is dropped.
+fn trimmed(samples: &[f64], outliers: f64) -> (f64, f64, usize) {
+  let mut samples = samples.to_vec();
+  samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  let dropped = (samples.len() as f64 * outliers).ceil() as usize;
+  let kept = &samples[..(samples.len() - dropped).max(1)];
+  let n = kept.len() as f64;
+  let mean = kept.iter().sum::<f64>() / n;
+  let variance = kept.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+  (mean, variance.sqrt(), kept.len())
+}
+
+fn median(values: &[f64]) -> f64 {
+  let mut values = values.to_vec();
+  values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  values[values.len() / 2]
+}
+
+impl TimingAttack {
+  pub fn new(length: usize, leak: Duration) -> Self {
+    TimingAttack {
+      length,
+      leak,
+      min_samples: 3,
+      max_samples: 40,
+      outliers: 0.25,
+      confidence: 3.0,
+      max_backtracks: length,
+    }
+  }
+
+  /// Returns the secret once the oracle accepts it, or `None` if the leak
+  /// can't be told apart from the noise.
+  pub fn run<O: TimingOracle>(&self, oracle: &O) -> Option<Vec<u8>> {
+    let mut known = Vec::new();
+    let mut baselines: Vec<f64> = Vec::new();
+    let mut excluded = vec![HashSet::new(); self.length];
+    let mut backtracks = 0;
+
+    while known.len() < self.length {
+      let i = known.len();
+      let leak = match baselines.len() {
+        0 | 1 => self.leak.as_secs_f64(),
+        n => (baselines[n - 1] - baselines[0]) / (n - 1) as f64,
+      };
+      let (byte, baseline) = match self.guess_byte(oracle, &known, &excluded[i], leak) {
+        Guess::Accepted(secret) => return Some(secret),
+        Guess::Byte { byte, baseline } => (Some(byte), baseline),
+        Guess::Nothing { baseline } => (None, baseline),
+      };
+
+      // With the bytes so far right, every guess here is a leak slower than
+      // the wrong guesses for the previous byte were. The last byte only
+      // shows itself by being accepted.
+      let prefix_right =
+        i + 1 < self.length && (i == 0 || baseline - baselines[i - 1] > leak / 2.0);
+      if let (Some(byte), true) = (byte, prefix_right) {
+        known.push(byte);
+        baselines.push(baseline);
+        continue;
+      }
+
+      if backtracks == self.max_backtracks {
+        return None;
+      }
+      backtracks += 1;
+      // Noise can hide the right byte, in which case it's worth another look
+      // at all of them
+      if prefix_right {
+        excluded[i].clear();
+        continue;
+      }
+      excluded[i].clear();
+      let wrong = known.pop()?;
+      baselines.pop();
+      excluded[i - 1].insert(wrong);
+    }
+    None
+  }
+
+  fn guess_byte<O: TimingOracle>(
+    &self,
+    oracle: &O,
+    known: &[u8],
+    excluded: &HashSet<u8>,
+    leak: f64,
+  ) -> Guess {
+    let fill = vec![0u8; self.length - known.len() - 1];
+    let candidates: Vec<u8> = (0..=255).filter(|c| !excluded.contains(c)).collect();
+    let mut alive = candidates.clone();
+    let mut rng = rand::thread_rng();
+    // Samples are kept relative to the median of the round they came from,
+    // as checks get quicker once fewer are in flight
+    let mut samples = vec![Vec::new(); 256];
+    let mut references = Vec::new();
+
+    let mut round = 0;
+    loop {
+      round += 1;
+      // At most one candidate is right, so padding the round out with
+      // rejected ones keeps its median the time of a wrong guess. Shuffle so
+      // that no candidate is always at the back of the queue.
+      let mut rejected: Vec<u8> = candidates
+        .iter()
+        .copied()
+        .filter(|c| !alive.contains(c))
+        .collect();
+      rejected.shuffle(&mut rng);
+      let mut order = alive.clone();
+      order.extend(rejected.iter().take(ROUND_SIZE.saturating_sub(alive.len())));
+      order.shuffle(&mut rng);
+
+      let guesses: Vec<_> = order
+        .iter()
+        .map(|&c| [known, &[c], &fill].concat())
+        .collect();
+      let mut times = Vec::new();
+      for (guess, timing) in guesses.iter().zip(oracle.time(&guesses)) {
+        match timing {
+          Timing::Accepted => return Guess::Accepted(guess.clone()),
+          Timing::Rejected(time) => times.push(time.as_secs_f64()),
+        }
+      }
+      let reference = median(&times);
+      references.push(reference);
+      for (&c, time) in order.iter().zip(times) {
+        samples[c as usize].push(time - reference);
+      }
+      if round < self.min_samples {
+        continue;
+      }
+
+      // Most candidates are wrong, so they set the noise. The right one
+      // should be about a leak slower than the rest.
+      let stats: Vec<_> = samples
+        .iter()
+        .map(|s| match s.len() {
+          0 => None,
+          _ => Some(trimmed(s, self.outliers)),
+        })
+        .collect();
+      let means: Vec<f64> = stats.iter().flatten().map(|s| s.0).collect();
+      let deviations: Vec<f64> = stats.iter().flatten().map(|s| s.1).collect();
+      // Only the first rounds try every candidate, so only they compare
+      // between bytes
+      let baseline = median(&references[..self.min_samples.max(1)]);
+      let noise = median(&deviations);
+      let midpoint = median(&means) + leak / 2.0;
+      let margin = |c: u8| {
+        let (mean, _, n) = stats[c as usize].unwrap();
+        (mean - midpoint, self.confidence * noise / (n as f64).sqrt())
+      };
+
+      // Only reject candidates that are clearly faster than the right one
+      alive.retain(|&c| {
+        let (above, error) = margin(c);
+        above + error > 0.0
+      });
+      let slowest = alive.iter().copied().max_by(|&a, &b| {
+        let a = margin(a).0;
+        a.partial_cmp(&margin(b).0).unwrap()
+      });
+
+      match slowest {
+        Some(byte) if alive.len() == 1 && margin(byte).0 > margin(byte).1 => {
+          return Guess::Byte { byte, baseline };
+        }
+        Some(_) if round < self.max_samples => (),
+        Some(byte) if margin(byte).0 > 0.0 => return Guess::Byte { byte, baseline },
+        _ => return Guess::Nothing { baseline },
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bytes::random_bytes;
+  use rand::rngs::StdRng;
+  use rand::{Rng, SeedableRng};
+  use std::cell::RefCell;
+
+  /// Compares byte by byte, taking `leak` per byte compared, with noise that
+  /// is usually small but now and then huge.
+  struct Simulated {
+    secret: Vec<u8>,
+    leak: Duration,
+    noise: Duration,
+    rng: RefCell<StdRng>,
+  }
+
+  impl Simulated {
+    fn new(secret: Vec<u8>, leak: Duration, noise: Duration) -> Self {
+      Simulated {
+        secret,
+        leak,
+        noise,
+        rng: RefCell::new(StdRng::seed_from_u64(31)),
+      }
+    }
+  }
+
+  impl TimingOracle for Simulated {
+    fn time(&self, guesses: &[Vec<u8>]) -> Vec<Timing> {
+      let mut rng = self.rng.borrow_mut();
+      guesses
+        .iter()
+        .map(|guess| {
+          if *guess == self.secret {
+            return Timing::Accepted;
+          }
+          let compared = 1
+            + guess
+              .iter()
+              .zip(&self.secret)
+              .take_while(|(a, b)| a == b)
+              .count();
+          let mut noise = self.noise.mul_f64(rng.gen::<f64>());
+          if rng.gen_ratio(1, 20) {
+            noise += self.leak * 10;
+          }
+          Timing::Rejected(self.leak * compared as u32 + noise)
+        })
+        .collect()
+    }
+  }
+
+  #[test]
+  fn recovers_secret_through_noise() {
+    let secret = random_bytes(20);
+    let leak = Duration::from_micros(1000);
+    let oracle = Simulated::new(secret.clone(), leak, leak * 2);
+    assert_eq!(TimingAttack::new(20, leak).run(&oracle), Some(secret));
+  }
+
+  /// Makes a wrong first byte look even slower than the right one, though
+  /// only while it's followed by the zeros the attack fills guesses with.
+  struct Decoy(Simulated, u8);
+
+  impl TimingOracle for Decoy {
+    fn time(&self, guesses: &[Vec<u8>]) -> Vec<Timing> {
+      let timings = self.0.time(guesses);
+      guesses
+        .iter()
+        .zip(timings)
+        .map(|(guess, timing)| match timing {
+          Timing::Rejected(time) if guess[0] == self.1 && guess[1..].iter().all(|&b| b == 0) => {
+            Timing::Rejected(time + self.0.leak * 2)
+          }
+          timing => timing,
+        })
+        .collect()
+    }
+  }
+
+  #[test]
+  fn backtracks_past_a_decoy() {
+    let mut secret = random_bytes(20);
+    secret[0] = 0x42;
+    let leak = Duration::from_micros(1000);
+    let oracle = Decoy(Simulated::new(secret.clone(), leak, leak), 0x24);
+    assert_eq!(TimingAttack::new(20, leak).run(&oracle), Some(secret));
+  }
+
+  #[test]
+  fn gives_up_without_a_leak() {
+    let secret = random_bytes(20);
+    let oracle = Simulated::new(
+      secret,
+      Duration::from_micros(0),
+      Duration::from_micros(1000),
+    );
+    let attack = TimingAttack::new(20, Duration::from_micros(1000));
+    assert_eq!(attack.run(&oracle), None);
+  }
+}
@@ -0,0 +1,118 @@
+//! Guesses MACs against an HMAC server, timing how long it takes to turn
+//! each one down.
+
+use cryptopals_rust::bytes::*;
+use cryptopals_rust::hmac_server::{self, Config};
+use cryptopals_rust::timing::{Timing, TimingAttack, TimingOracle};
+use std::io;
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tokio::task;
+
+/// A MAC check at the other end of an HTTP connection. Only blocks on
+/// requests, so it has to be used off the async runtime, for instance from
+/// `task::spawn_blocking`.
+pub struct RemoteMac {
+  client: reqwest::Client,
+  url: String,
+  concurrency: usize,
+  runtime: Handle,
+}
+
+impl RemoteMac {
+  /// `url` is everything up to the MAC, like `.../hmac/{file}`.
+  pub fn new(url: &str, concurrency: usize, runtime: Handle) -> Self {
+    RemoteMac {
+      client: reqwest::Client::new(),
+      url: url.to_string(),
+      concurrency,
+      runtime,
+    }
+  }
+}
+
+async fn time_check(request: reqwest::RequestBuilder) -> Timing {
+  let start = Instant::now();
+  let status = request
+    .send()
+    .await
+    .expect("MAC check request failed")
+    .status()
+    .as_u16();
+  match status {
+    200 => Timing::Accepted,
+    500 => Timing::Rejected(start.elapsed()),
+    _ => panic!("Unexpected status code {}", status),
+  }
+}
+
+impl TimingOracle for RemoteMac {
+  fn time(&self, guesses: &[Vec<u8>]) -> Vec<Timing> {
+    self.runtime.block_on(async {
+      let mut timings = Vec::new();
+      for batch in guesses.chunks(self.concurrency) {
+        let requests: Vec<_> = batch
+          .iter()
+          .map(|mac| {
+            let request = self.client.get(format!("{}/{}", self.url, mac.to_hex()));
+            task::spawn(time_check(request))
+          })
+          .collect();
+        for request in requests {
+          timings.push(request.await.expect("MAC check request panicked"));
+        }
+      }
+      timings
+    })
+  }
+}
+
+/// Runs the attack against the MAC check at `url`, with up to `concurrency`
+/// requests in flight at once.
+pub async fn attack(url: &str, attack: TimingAttack, concurrency: usize) -> Option<Vec<u8>> {
+  let oracle = RemoteMac::new(url, concurrency, Handle::current());
+  task::spawn_blocking(move || attack.run(&oracle))
+    .await
+    .ok()?
+}
+
+/// Starts an HMAC server on a free port and runs the attack against its
+/// check of `file`.
+pub async fn attack_server(
+  config: Config,
+  file: &str,
+  timing_attack: TimingAttack,
+  concurrency: usize,
+) -> io::Result<Option<Vec<u8>>> {
+  let (server, addr) = hmac_server::start("127.0.0.1:0", config)?;
+  let url = format!("http://{}/hmac/{}", addr, file);
+  let hmac = attack(&url, timing_attack, concurrency).await;
+  server.stop(false).await;
+  Ok(hmac)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cryptopals_rust::hmac_server::Comparison;
+  use std::time::Duration;
+
+  #[tokio::test]
+  async fn secure_comparisons_give_nothing_away() {
+    let delay = Duration::from_millis(1);
+    for &comparison in &[Comparison::ConstantTime, Comparison::DoubleHmac] {
+      let config = Config {
+        delay,
+        comparison,
+        ..Config::new(&random_64())
+      };
+      let attack = TimingAttack {
+        max_samples: 6,
+        max_backtracks: 1,
+        ..TimingAttack::new(20, delay)
+      };
+      let found = attack_server(config, "hello!", attack, 64).await.unwrap();
+      assert_eq!(found, None);
+    }
+  }
+}
@@ -0,0 +1,155 @@
+//! Word lists for dictionary attacks, and rules for the variations on each
+//! word that people actually pick as passwords.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Common English words, built in so there's always a list to fall back on.
+pub const EMBEDDED: &str = include_str!("../data/words.txt");
+
+/// Where `search_paths` looks after the paths in this variable, which is a
+/// list like `PATH`.
+pub const PATHS_VAR: &str = "CRYPTOPALS_WORDS";
+
+/// Where word lists usually are.
+pub const SYSTEM_PATHS: &[&str] = &["/usr/share/dict/words", "/usr/dict/words"];
+
+/// One word per line, ignoring blank lines and surrounding whitespace.
+pub fn parse(text: &str) -> Vec<String> {
+  text
+    .lines()
+    .map(str::trim)
+    .filter(|word| !word.is_empty())
+    .map(String::from)
+    .collect()
+}
+
+pub fn embedded() -> Vec<String> {
+  parse(EMBEDDED)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<String>> {
+  Ok(parse(&fs::read_to_string(path)?))
+}
+
+/// The paths in `PATHS_VAR`, then `SYSTEM_PATHS`.
+pub fn search_paths() -> Vec<PathBuf> {
+  let configured: Vec<PathBuf> = env::var_os(PATHS_VAR)
+    .map(|paths| env::split_paths(&paths).collect())
+    .unwrap_or_default();
+  configured
+    .into_iter()
+    .chain(SYSTEM_PATHS.iter().map(PathBuf::from))
+    .collect()
+}
+
+/// The first list in `paths` that can be read and has any words in it, or
+/// the embedded list when none does.
+pub fn load_first<P: AsRef<Path>>(paths: &[P]) -> Vec<String> {
+  paths
+    .iter()
+    .filter_map(|path| load(path).ok())
+    .find(|words| !words.is_empty())
+    .unwrap_or_else(embedded)
+}
+
+/// A way of turning a word into other candidate passwords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+  Lowercase,
+  Uppercase,
+  /// Uppercases just the first letter.
+  Capitalize,
+  /// Swaps letters for the digits that look like them, all at once.
+  Leetspeak,
+  /// Appends every number of up to this many digits, leading zeros and all.
+  AppendDigits(u32),
+}
+
+impl Rule {
+  pub fn apply(&self, word: &str) -> Vec<String> {
+    match *self {
+      Rule::Lowercase => vec![word.to_lowercase()],
+      Rule::Uppercase => vec![word.to_uppercase()],
+      Rule::Capitalize => {
+        let mut chars = word.chars();
+        match chars.next() {
+          Some(first) => vec![first.to_uppercase().chain(chars).collect()],
+          None => vec![String::new()],
+        }
+      }
+      Rule::Leetspeak => vec![word
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+          'a' => '4',
+          'e' => '3',
+          'i' => '1',
+          'o' => '0',
+          's' => '5',
+          't' => '7',
+          _ => c,
+        })
+        .collect()],
+      Rule::AppendDigits(digits) => (1..=digits)
+        .flat_map(|width| {
+          (0..10u64.pow(width))
+            .map(move |n| format!("{}{:0width$}", word, n, width = width as usize))
+        })
+        .collect(),
+    }
+  }
+}
+
+/// Every word followed by what each of `rules` makes of it, leaving out
+/// repeats of a candidate from the same word.
+pub fn mutate(words: &[String], rules: &[Rule]) -> Vec<String> {
+  let mut candidates = Vec::new();
+  for word in words {
+    let start = candidates.len();
+    for candidate in rules.iter().flat_map(|rule| rule.apply(word)) {
+      if candidate != *word && !candidates[start..].contains(&candidate) {
+        candidates.push(candidate);
+      }
+    }
+    candidates.insert(start, word.clone());
+  }
+  candidates
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn falls_back_to_the_embedded_list() {
+    let words = load_first(&["/nonexistent/words"]);
+    assert_eq!(words, embedded());
+    assert!(words.len() > 1000);
+    assert!(words
+      .iter()
+      .all(|word| word.trim() == word && !word.is_empty()));
+  }
+
+  #[test]
+  fn rules_make_passwords() {
+    assert_eq!(Rule::Capitalize.apply("ice"), vec!["Ice"]);
+    assert_eq!(Rule::Uppercase.apply("ice"), vec!["ICE"]);
+    assert_eq!(Rule::Leetspeak.apply("Pastel"), vec!["P4573l"]);
+    let digits = Rule::AppendDigits(2).apply("ice");
+    assert_eq!(digits.len(), 110);
+    assert_eq!(&digits[..2], ["ice0", "ice1"]);
+    assert_eq!(&digits[10..12], ["ice00", "ice01"]);
+  }
+
+  #[test]
+  fn mutations_skip_repeats() {
+    let words = vec!["ice".to_string(), "Vanilla".to_string()];
+    let rules = [Rule::Lowercase, Rule::Capitalize, Rule::AppendDigits(1)];
+    let candidates = mutate(&words, &rules);
+    assert_eq!(candidates.len(), 2 * 12);
+    assert_eq!(&candidates[..3], ["ice", "Ice", "ice0"]);
+    assert_eq!(&candidates[12..14], ["Vanilla", "vanilla"]);
+  }
+}